@@ -0,0 +1,212 @@
+use crate::{breaks::Breaks, period::Period, sullygnome::StreamData, weekly::weekday_name};
+use anyhow::{anyhow, Context, Error, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::{cmp::Reverse, str::FromStr};
+
+/// Streams starting this early still count for a slot.
+const EARLY_START_MIN: i64 = 60;
+/// Streams starting this late still count for a slot.
+const LATE_START_MIN: i64 = 4 * 60;
+
+/// A weekly slot in the channel's timezone, e.g. `Mon 18:00`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ScheduledSlot {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+}
+
+/// The announced weekly schedule, e.g. `Mon 18:00, Wed 18:00, Fri 18:00`.
+#[derive(Debug, Default, Clone)]
+pub struct DeclaredSchedule {
+    pub slots: Vec<ScheduledSlot>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Adherence {
+    pub schedule: String,
+    pub slots: usize,
+    pub kept: usize,
+    pub percent: f64,
+    /// Average delay of the kept slots, negative if streams started early.
+    pub average_delay_min: i64,
+    /// All past slots in the period, from newest to oldest.
+    pub results: Vec<SlotResult>,
+    pub missed: Vec<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotResult {
+    pub at: DateTime<Utc>,
+    pub kept: bool,
+    pub delay_min: Option<i64>,
+    pub length_min: Option<i64>,
+}
+
+impl FromStr for ScheduledSlot {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (weekday, time) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("Expected a slot like 'Mon 18:00', got '{s}'"))?;
+        Ok(Self {
+            weekday: weekday
+                .parse()
+                .map_err(|_| anyhow!("Invalid weekday '{weekday}'"))?,
+            time: NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .with_context(|| format!("Invalid time '{time}'"))?,
+        })
+    }
+}
+
+impl FromStr for DeclaredSchedule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Self {
+            slots: s
+                .split(',')
+                .filter(|slot| !slot.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl DeclaredSchedule {
+    fn describe(&self) -> String {
+        self.slots
+            .iter()
+            .map(|slot| {
+                format!(
+                    "{} {}",
+                    weekday_name(slot.weekday),
+                    slot.time.format("%H:%M")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl Adherence {
+    /// Matches every slot of the schedule that already passed in `period` with a stream.
+    /// Slots during announced breaks are skipped.
+    /// Returns `None` if there's no schedule.
+    pub fn calculate(
+        period: Period,
+        schedule: &DeclaredSchedule,
+        timezone: Tz,
+        breaks: &Breaks,
+        streams: &[StreamData],
+    ) -> Option<Self> {
+        if schedule.slots.is_empty() {
+            return None;
+        }
+        let (start, end) = period.bounds();
+        let end = end.min(Utc::now());
+
+        let mut results = Vec::new();
+        // local days can start before the period in UTC
+        for day in (start.date_naive() - Duration::days(1))
+            .iter_days()
+            .take(period.days() + 2)
+        {
+            for slot in schedule.slots.iter().filter(|s| s.weekday == day.weekday()) {
+                let Some(at) = timezone
+                    .from_local_datetime(&day.and_time(slot.time))
+                    .earliest()
+                    .map(|at| at.with_timezone(&Utc))
+                else {
+                    continue;
+                };
+                if at < start || at >= end || breaks.contains(day) {
+                    continue;
+                }
+                let stream = streams
+                    .iter()
+                    .filter(|stream| {
+                        let delay = (stream.start_date_time - at).num_minutes();
+                        (-EARLY_START_MIN..LATE_START_MIN).contains(&delay)
+                            || (stream.start_date_time < at && stream.end_date_time() > at)
+                    })
+                    .min_by_key(|stream| (stream.start_date_time - at).num_minutes().abs());
+                results.push(SlotResult {
+                    at,
+                    kept: stream.is_some(),
+                    delay_min: stream.map(|stream| (stream.start_date_time - at).num_minutes()),
+                    length_min: stream.map(|stream| stream.length),
+                });
+            }
+        }
+        results.sort_by_key(|result| Reverse(result.at));
+
+        let kept = results.iter().filter(|result| result.kept).count();
+        let delays = results
+            .iter()
+            .filter_map(|result| result.delay_min)
+            .collect::<Vec<_>>();
+        Some(Self {
+            schedule: schedule.describe(),
+            slots: results.len(),
+            kept,
+            percent: if results.is_empty() {
+                1.0
+            } else {
+                kept as f64 / results.len() as f64
+            },
+            average_delay_min: delays
+                .iter()
+                .sum::<i64>()
+                .checked_div(delays.len() as i64)
+                .unwrap_or_default(),
+            missed: results
+                .iter()
+                .filter(|result| !result.kept)
+                .map(|result| result.at)
+                .collect(),
+            results,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        adherence::{Adherence, DeclaredSchedule},
+        breaks::Breaks,
+        period::Period,
+        sullygnome::StreamData,
+        weekly::DEFAULT_TIMEZONE,
+    };
+
+    #[test]
+    fn matches_slots() {
+        let schedule = "Mon 18:00, Wed 18:00".parse::<DeclaredSchedule>().unwrap();
+        let adherence = Adherence::calculate(
+            Period::month(2022, 1).unwrap(),
+            &schedule,
+            DEFAULT_TIMEZONE,
+            &Breaks::default(),
+            &[StreamData {
+                // Wednesday, 18:30 in London (GMT)
+                start_date_time: "2022-01-05T18:30:00Z".parse().unwrap(),
+                length: 120,
+                games: Vec::new(),
+                clamped: false,
+            }],
+        )
+        .unwrap();
+        // 5 Mondays and 4 Wednesdays
+        assert_eq!(adherence.slots, 9);
+        assert_eq!(adherence.kept, 1);
+        assert_eq!(adherence.average_delay_min, 30);
+        assert_eq!(adherence.missed.len(), 8);
+        assert!("Mon".parse::<DeclaredSchedule>().is_err());
+    }
+}
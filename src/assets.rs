@@ -0,0 +1,126 @@
+use actix_files::Files;
+use actix_web::{web::ServiceConfig, Route};
+use anyhow::{Context, Result};
+use handlebars::{DirectorySourceOptions, Handlebars};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const TEMPLATE_EXTENSION: &str = ".hbs.html";
+
+/// Where templates or static files are loaded from.
+#[derive(Debug, Clone)]
+pub enum Assets {
+    Disk(PathBuf),
+    /// Compiled into the binary.
+    #[cfg(feature = "embed")]
+    Embedded,
+}
+
+#[cfg(feature = "embed")]
+mod embedded {
+    use actix_web::HttpResponse;
+    use rust_embed::RustEmbed;
+    use std::borrow::Cow;
+
+    #[derive(RustEmbed)]
+    #[folder = "templates"]
+    pub struct Templates;
+
+    #[derive(RustEmbed)]
+    #[folder = "static"]
+    pub struct Static;
+
+    pub async fn serve(path: Cow<'static, str>) -> HttpResponse {
+        match Static::get(&path) {
+            Some(file) => HttpResponse::Ok()
+                .content_type(file.metadata.mimetype())
+                .body(file.data.into_owned()),
+            None => HttpResponse::NotFound().finish(),
+        }
+    }
+}
+
+impl Assets {
+    /// Templates are files ending in [`TEMPLATE_EXTENSION`],
+    /// their name (without the extension) can be used as a partial.
+    pub fn register_templates(&self, handlebars: &mut Handlebars) -> Result<()> {
+        match self {
+            Assets::Disk(dir) => handlebars
+                .register_templates_directory(dir, {
+                    let mut opts = DirectorySourceOptions::default();
+                    opts.tpl_extension = TEMPLATE_EXTENSION.to_owned();
+                    opts
+                })
+                .with_context(|| format!("Failed to load the templates from {}", dir.display())),
+            #[cfg(feature = "embed")]
+            Assets::Embedded => handlebars
+                .register_embed_templates_with_extension::<embedded::Templates>(TEMPLATE_EXTENSION)
+                .context("Failed to load the embedded templates"),
+        }
+    }
+
+    /// Serves the static files at `/`. Everything else is handled by `fallback`.
+    pub fn configure_static(&self, cfg: &mut ServiceConfig, fallback: Route) {
+        match self {
+            Assets::Disk(dir) => {
+                cfg.service(
+                    Files::new("/", dir)
+                        .index_file("this_file_doesnt_exist_but_we_dont_need_it")
+                        .default_handler(fallback),
+                );
+            }
+            #[cfg(feature = "embed")]
+            Assets::Embedded => {
+                use actix_web::web;
+
+                for path in embedded::Static::iter() {
+                    cfg.route(
+                        &format!("/{path}"),
+                        web::get().to(move || embedded::serve(path.clone())),
+                    );
+                }
+                cfg.default_service(fallback);
+            }
+        }
+    }
+
+    /// Copies all files into `out`, keeping their relative paths.
+    pub fn copy_to(&self, out: &Path) -> Result<()> {
+        match self {
+            Assets::Disk(dir) => copy_dir(dir, out),
+            #[cfg(feature = "embed")]
+            Assets::Embedded => {
+                for path in embedded::Static::iter() {
+                    let file = embedded::Static::get(&path)
+                        .with_context(|| format!("Missing embedded file {path}"))?;
+                    let target = out.join(&*path);
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&target, file.data)
+                        .with_context(|| format!("Failed to write {}", target.display()))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("Failed to create {}", to.display()))?;
+    let entries =
+        fs::read_dir(from).with_context(|| format!("Failed to read {}", from.display()))?;
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Announced time off that doesn't count as ditched.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct Break {
+    pub from: NaiveDate,
+    /// Inclusive.
+    pub to: NaiveDate,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Breaks {
+    #[serde(default)]
+    pub breaks: Vec<Break>,
+}
+
+impl Breaks {
+    /// Loads breaks from an iCalendar file (`.ics`) or a TOML file with `[[breaks]]` tables.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read breaks from {}", path.display()))?;
+        let breaks = if path.extension().is_some_and(|ext| ext == "ics") {
+            Self {
+                breaks: parse_ics(&content)?,
+            }
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("Invalid breaks in {}", path.display()))?
+        };
+        if let Some(bad) = breaks.breaks.iter().find(|b| b.from > b.to) {
+            return Err(anyhow!(
+                "The break from {} to {} ends before it starts",
+                bad.from,
+                bad.to
+            ));
+        }
+        Ok(breaks)
+    }
+
+    pub fn contains(&self, day: NaiveDate) -> bool {
+        self.breaks.iter().any(|b| b.from <= day && day <= b.to)
+    }
+}
+
+/// Reads the `VEVENT`s of a calendar.
+/// Only the dates are used, times and timezones are ignored.
+fn parse_ics(content: &str) -> Result<Vec<Break>> {
+    // long lines are folded by starting the next line with a space or tab
+    let mut lines = Vec::<String>::new();
+    for line in content.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(continued) if !lines.is_empty() => lines.last_mut().unwrap().push_str(continued),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    let mut breaks = Vec::new();
+    let mut event: Option<(Option<NaiveDate>, Option<NaiveDate>, Option<String>)> = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // properties can have parameters like `DTSTART;VALUE=DATE`
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        match (name, &mut event) {
+            ("BEGIN", None) if value == "VEVENT" => event = Some((None, None, None)),
+            ("DTSTART", Some((start, ..))) => *start = Some(parse_ics_date(value)?),
+            ("DTEND", Some((_, end, _))) => {
+                let date = parse_ics_date(value)?;
+                // the end of all-day events is exclusive
+                *end = Some(if params.contains("VALUE=DATE") && !value.contains('T') {
+                    date.checked_sub_days(Days::new(1)).unwrap_or(date)
+                } else {
+                    date
+                });
+            }
+            ("SUMMARY", Some((.., summary))) => *summary = Some(value.to_owned()),
+            ("END", Some(_)) if value == "VEVENT" => {
+                if let Some((Some(from), end, reason)) = event.take() {
+                    breaks.push(Break {
+                        from,
+                        to: end.unwrap_or(from).max(from),
+                        reason,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(breaks)
+}
+
+fn parse_ics_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8).unwrap_or(value), "%Y%m%d")
+        .with_context(|| format!("Invalid date in calendar: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::breaks::{parse_ics, Break};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn parses_ics() {
+        let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+        let breaks = parse_ics(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20240701\r\n\
+             DTEND;VALUE=DATE:20240715\r\n\
+             SUMMARY:Vaca\r\n \
+             tion\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART:20241224T100000Z\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            breaks,
+            vec![
+                Break {
+                    from: date("2024-07-01"),
+                    to: date("2024-07-14"),
+                    reason: Some("Vacation".to_owned()),
+                },
+                Break {
+                    from: date("2024-12-24"),
+                    to: date("2024-12-24"),
+                    reason: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{datetime::start_of_date, model::StreamerModel, period::Period};
+use actix_web::{
+    http::header::{
+        self, CacheControl, CacheDirective, ETag, EntityTag, Header, IfModifiedSince, IfNoneMatch,
+        LastModified,
+    },
+    HttpRequest, HttpResponse, HttpResponseBuilder,
+};
+use chrono::{DateTime, Datelike, Utc};
+use std::{sync::Arc, time::SystemTime};
+
+/// Responses with data of the current year are cached shortly, since it's refetched regularly.
+const SHORT_MAX_AGE: u32 = 60;
+/// Data of previous years is only fetched on startup.
+const LONG_MAX_AGE: u32 = 24 * 60 * 60;
+
+/// Caching headers of a response, derived from the time its data was fetched.
+pub struct Validators {
+    etag: EntityTag,
+    last_modified: SystemTime,
+    max_age: u32,
+}
+
+impl Validators {
+    /// `version` changes the ETag without a new fetch (e.g. when the templates are reloaded).
+    /// `is_final` responses don't include the current year, so they're cached longer.
+    ///
+    /// Responses with the current year also depend on the time they're rendered
+    /// (e.g. the projection or an ongoing ditch), so they change at least once a day.
+    /// Changes within a day are only picked up when the data is fetched again.
+    pub fn new(fetched_at: DateTime<Utc>, version: u64, is_final: bool) -> Self {
+        Self::at(fetched_at, version, is_final, Utc::now())
+    }
+
+    fn at(fetched_at: DateTime<Utc>, version: u64, is_final: bool, now: DateTime<Utc>) -> Self {
+        let (etag, modified) = match is_final {
+            true => (
+                format!("{:x}-{version}", fetched_at.timestamp_millis()),
+                fetched_at,
+            ),
+            false => {
+                let today = now.date_naive();
+                (
+                    format!(
+                        "{:x}-{version}-{:x}",
+                        fetched_at.timestamp_millis(),
+                        today.num_days_from_ce()
+                    ),
+                    fetched_at.max(start_of_date(today)),
+                )
+            }
+        };
+        Self {
+            etag: EntityTag::new_weak(etag),
+            // HTTP dates don't have fractional seconds
+            last_modified: DateTime::from_timestamp(modified.timestamp(), 0)
+                .unwrap_or(modified)
+                .into(),
+            max_age: match is_final {
+                true => LONG_MAX_AGE,
+                false => SHORT_MAX_AGE,
+            },
+        }
+    }
+
+    /// A `304 Not Modified` response if the client's copy is still up to date.
+    pub fn not_modified(&self, req: &HttpRequest) -> Option<HttpResponse> {
+        // If-Modified-Since is ignored if If-None-Match is present
+        let fresh = if req.headers().contains_key(header::IF_NONE_MATCH) {
+            match IfNoneMatch::parse(req) {
+                Ok(IfNoneMatch::Any) => true,
+                Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&self.etag)),
+                Err(_) => false,
+            }
+        } else {
+            IfModifiedSince::parse(req)
+                .is_ok_and(|IfModifiedSince(since)| SystemTime::from(since) >= self.last_modified)
+        };
+        fresh.then(|| self.headers(&mut HttpResponse::NotModified()).finish())
+    }
+
+    pub fn headers<'a>(&self, res: &'a mut HttpResponseBuilder) -> &'a mut HttpResponseBuilder {
+        res.insert_header(ETag(self.etag.clone()))
+            .insert_header(LastModified(self.last_modified.into()))
+            .insert_header(CacheControl(vec![
+                CacheDirective::Public,
+                CacheDirective::MaxAge(self.max_age),
+            ]))
+    }
+}
+
+/// Periods that don't include the current year never change.
+pub fn is_final(period: Period) -> bool {
+    !period.years().contains(&Utc::now().year())
+}
+
+/// Pages with all years (e.g. comparisons) change with the current year.
+pub fn all_years_validators(
+    models: &[Arc<StreamerModel>],
+    version: u64,
+    is_final: bool,
+) -> Validators {
+    let fetched_at = models
+        .iter()
+        .map(|model| model.fetched_at)
+        .max()
+        .unwrap_or_default();
+    Validators::new(fetched_at, version, is_final)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        caching::{all_years_validators, is_final, Validators},
+        model::{ModelOptions, StreamerModel, Surrounding},
+        period::Period,
+    };
+    use actix_web::{http::StatusCode, test::TestRequest};
+    use chrono::{Datelike, Duration, TimeZone, Utc};
+    use std::{sync::Arc, time::SystemTime};
+
+    #[test]
+    fn handles_conditional_requests() {
+        let fetched_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let validators = Validators::new(fetched_at, 0, true);
+
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", "W/\"18df9e2b200-0\""))
+            .to_http_request();
+        let res = validators.not_modified(&req).unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", "W/\"18df9e2b200-1\""))
+            .insert_header(("If-Modified-Since", "Fri, 01 Mar 2024 12:00:00 GMT"))
+            .to_http_request();
+        assert!(validators.not_modified(&req).is_none());
+
+        let req = TestRequest::default()
+            .insert_header(("If-Modified-Since", "Fri, 01 Mar 2024 12:00:00 GMT"))
+            .to_http_request();
+        assert!(validators.not_modified(&req).is_some());
+        let req = TestRequest::default()
+            .insert_header(("If-Modified-Since", "Fri, 01 Mar 2024 11:59:59 GMT"))
+            .to_http_request();
+        assert!(validators.not_modified(&req).is_none());
+    }
+
+    #[test]
+    fn changes_daily_with_the_current_year() {
+        let fetched_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let today = Validators::at(fetched_at, 0, false, fetched_at + Duration::hours(1));
+        let later = Validators::at(fetched_at, 0, false, fetched_at + Duration::hours(2));
+        let tomorrow = Validators::at(fetched_at, 0, false, fetched_at + Duration::days(1));
+        assert_eq!(today.etag, later.etag);
+        assert_ne!(today.etag, tomorrow.etag);
+        assert_eq!(today.last_modified, SystemTime::from(fetched_at));
+        assert_eq!(
+            tomorrow.last_modified,
+            SystemTime::from(Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap())
+        );
+        assert!(today.max_age < Validators::new(fetched_at, 0, true).max_age);
+    }
+
+    #[test]
+    fn only_past_periods_are_final() {
+        let year = Utc::now().year();
+        assert!(is_final(Period::Year(year - 1)));
+        assert!(is_final(Period::month(year - 1, 12).unwrap()));
+        assert!(!is_final(Period::CurrentYear));
+        assert!(!is_final(Period::LastDays(30)));
+        assert!(!is_final(Period::AllTime { since: year - 1 }));
+    }
+
+    #[test]
+    fn uses_the_newest_fetch() {
+        let fetched_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let models = [fetched_at, fetched_at - Duration::days(30)]
+            .map(|fetched_at| {
+                let mut model = StreamerModel::create(
+                    Period::Year(2023),
+                    &ModelOptions::default(),
+                    Vec::new(),
+                    Vec::new(),
+                    Surrounding::default(),
+                )
+                .unwrap();
+                model.fetched_at = fetched_at;
+                Arc::new(model)
+            })
+            .to_vec();
+        let validators = all_years_validators(&models, 2, true);
+        assert_eq!(validators.etag, Validators::new(fetched_at, 2, true).etag);
+        assert_eq!(validators.last_modified, SystemTime::from(fetched_at));
+        assert!(all_years_validators(&models, 2, false).max_age < validators.max_age);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{period::Period, sullygnome::StreamData};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryTimeline {
+    /// Sorted by the first stream.
+    pub categories: Vec<CategorySpan>,
+    /// Number of categories that were never streamed before this period.
+    pub new_categories: usize,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorySpan {
+    pub category: String,
+    pub category_image: String,
+    pub first_streamed: DateTime<Utc>,
+    pub last_streamed: DateTime<Utc>,
+    pub streams: usize,
+    /// Start of the span relative to the period (0-1).
+    pub offset: f64,
+    /// Length of the span relative to the period (0-1).
+    pub width: f64,
+    pub new: bool,
+}
+
+impl CategoryTimeline {
+    pub fn calculate(period: Period, streams: &[StreamData]) -> Self {
+        let (start, end) = period.bounds();
+        let length = (end - start).num_minutes().max(1) as f64;
+        let relative =
+            |time: DateTime<Utc>| ((time - start).num_minutes() as f64 / length).clamp(0.0, 1.0);
+
+        let mut spans = HashMap::<&str, CategorySpan>::new();
+        for stream in streams {
+            let stream_end = stream.end_date_time();
+            for game in &stream.games {
+                let span = spans.entry(&game.category).or_insert_with(|| CategorySpan {
+                    category: game.category.clone(),
+                    category_image: game.category_image.clone(),
+                    first_streamed: stream.start_date_time,
+                    last_streamed: stream_end,
+                    streams: 0,
+                    offset: 0.0,
+                    width: 0.0,
+                    new: false,
+                });
+                span.first_streamed = span.first_streamed.min(stream.start_date_time);
+                span.last_streamed = span.last_streamed.max(stream_end);
+                span.streams += 1;
+            }
+        }
+
+        let mut categories = spans.into_values().collect::<Vec<_>>();
+        for span in &mut categories {
+            span.offset = relative(span.first_streamed);
+            span.width = relative(span.last_streamed) - span.offset;
+        }
+        categories.sort_by_key(|span| span.first_streamed);
+        Self {
+            categories,
+            new_categories: 0,
+        }
+    }
+
+    /// Marks every category that isn't in `seen` as new.
+    /// Nothing is marked if nothing was seen before, as there's no history to compare to.
+    pub fn mark_new(&mut self, seen: &HashSet<&str>) {
+        if seen.is_empty() {
+            return;
+        }
+        for span in &mut self.categories {
+            span.new = !seen.contains(span.category.as_str());
+        }
+        self.new_categories = self.categories.iter().filter(|span| span.new).count();
+    }
+}
+
+/// All categories played in `streams`.
+pub fn played<'a>(streams: impl IntoIterator<Item = &'a StreamData>) -> HashSet<&'a str> {
+    streams
+        .into_iter()
+        .flat_map(|stream| &stream.games)
+        .map(|game| game.category.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        categories::{played, CategoryTimeline},
+        period::Period,
+        sullygnome::{StreamData, StreamGame},
+    };
+
+    #[test]
+    fn finds_new_categories() {
+        let stream = |start: &str, category: &str| StreamData {
+            start_date_time: start.parse().unwrap(),
+            length: 60,
+            games: vec![StreamGame {
+                category: category.to_owned(),
+                category_image: String::new(),
+            }],
+            clamped: false,
+        };
+        let before = [stream("2021-05-01T18:00:00Z", "Overwatch 2")];
+        let mut timeline = CategoryTimeline::calculate(
+            Period::Year(2022),
+            &[
+                stream("2022-12-31T12:00:00Z", "Overwatch 2"),
+                stream("2022-07-02T12:00:00Z", "Minecraft"),
+                stream("2022-01-01T00:00:00Z", "Overwatch 2"),
+            ],
+        );
+        timeline.mark_new(&played(&before));
+
+        let [overwatch, minecraft] = timeline.categories.as_slice() else {
+            panic!("expected two categories");
+        };
+        assert_eq!(overwatch.category, "Overwatch 2");
+        assert_eq!(overwatch.streams, 2);
+        assert_eq!(overwatch.offset, 0.0);
+        assert!(overwatch.width > 0.99);
+        assert!(!overwatch.new);
+        assert!(minecraft.new);
+        assert_eq!(timeline.new_categories, 1);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::model::StreamerModel;
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct YearComparison<'a> {
+    pub streamer: &'a StreamerModel,
+    /// Difference to the previous year in the comparison.
+    pub delta: Option<Delta>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Delta {
+    /// In percentage points.
+    pub variety_points: f64,
+    pub days_ditched: i64,
+    pub hours_streamed: f64,
+}
+
+impl Delta {
+    pub fn between(from: &StreamerModel, to: &StreamerModel) -> Self {
+        Self {
+            variety_points: (to.variety_percent - from.variety_percent) * 100.0,
+            days_ditched: to.days_ditched as i64 - from.days_ditched as i64,
+            hours_streamed: (to.total_time_min as f64 - from.total_time_min as f64) / 60.0,
+        }
+    }
+}
+
+/// Compares each model to the one before it.
+pub fn compare<'a>(models: &[&'a StreamerModel]) -> Vec<YearComparison<'a>> {
+    models
+        .iter()
+        .enumerate()
+        .map(|(i, streamer)| YearComparison {
+            streamer,
+            delta: i
+                .checked_sub(1)
+                .map(|prev| Delta::between(models[prev], streamer)),
+        })
+        .collect()
+}
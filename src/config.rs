@@ -0,0 +1,364 @@
+use crate::{
+    adherence::DeclaredSchedule,
+    assets::Assets,
+    breaks::Breaks,
+    data_actor::{FetchOptions, DEFAULT_CACHE_TIME, DEFAULT_FROM_YEAR},
+    diversity::Metric,
+    model::{ModelOptions, DEFAULT_VARIETY_THRESHOLD},
+    sullygnome::{DEFAULT_CHANNEL_ID, DEFAULT_CHANNEL_NAME},
+    templates::Site,
+    weekly::DEFAULT_TIMEZONE,
+};
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Utc};
+use chrono_tz::Tz;
+use clap::{Args, Parser, Subcommand};
+use serde::{Deserialize, Deserializer};
+use std::{
+    fmt::Display,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+/// Used if no config file is passed and this file exists.
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Tracks if m0xyy has streamed more variety than Overwatch.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path to a TOML config file [default: config.toml if it exists]
+    #[arg(long, short, env = "CONFIG_FILE")]
+    pub config: Option<PathBuf>,
+    #[command(flatten)]
+    pub overrides: Overrides,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Runs the webserver (default)
+    Serve,
+    /// Renders the pages of all years and the API into a directory that can be hosted statically
+    Generate {
+        /// Output directory
+        #[arg(long, short, default_value = "dist")]
+        out: PathBuf,
+    },
+    /// Prints a summary of a year
+    Status {
+        /// The year to summarize [default: the current year]
+        #[arg(long)]
+        year: Option<i32>,
+        /// Print the model as JSON (like the API)
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Settings that override the config file.
+#[derive(Args, Debug, Default)]
+pub struct Overrides {
+    /// Address the webserver listens on
+    #[arg(long, env = "BIND")]
+    pub bind: Option<SocketAddr>,
+    /// How long the data of the current year is cached (e.g. `10m`)
+    #[arg(long, env = "CACHE_TIME", value_parser = humantime::parse_duration)]
+    pub cache_time: Option<Duration>,
+    /// The first year that's tracked
+    #[arg(long, env = "FROM_YEAR")]
+    pub from_year: Option<i32>,
+    /// SullyGnome id of the channel
+    #[arg(long, env = "CHANNEL_ID")]
+    pub channel_id: Option<u64>,
+    /// Name of the channel on SullyGnome (used in links)
+    #[arg(long, env = "CHANNEL_NAME")]
+    pub channel_name: Option<String>,
+    /// IANA timezone of the schedule and the weekly heatmap (e.g. `Europe/London`)
+    #[arg(long, env = "TIMEZONE")]
+    pub timezone: Option<Tz>,
+    /// Directory with the handlebars templates [default: embedded or templates]
+    #[arg(long, env = "TEMPLATE_DIR")]
+    pub template_dir: Option<PathBuf>,
+    /// Directory with the static files [default: embedded or static]
+    #[arg(long, env = "STATIC_DIR")]
+    pub static_dir: Option<PathBuf>,
+    /// The minimum share of variety to count as variety (0-1)
+    #[arg(long, env = "VARIETY_THRESHOLD")]
+    pub variety_threshold: Option<f64>,
+    /// top-game, top-three, evenness or gini
+    #[arg(long, env = "HEADLINE_METRIC")]
+    pub headline_metric: Option<Metric>,
+    /// Streams shorter than this (in minutes) are counted as short streams
+    #[arg(long, env = "SHORT_STREAM_MINUTES")]
+    pub short_stream_minutes: Option<i64>,
+    /// iCalendar or TOML file with announced breaks
+    #[arg(long, env = "BREAKS_FILE")]
+    pub breaks_file: Option<PathBuf>,
+    /// The announced weekly schedule (e.g. `Mon 18:00, Wed 18:00`)
+    #[arg(long, env = "SCHEDULE")]
+    pub schedule: Option<String>,
+    /// Read the templates again on every render (new template files need a restart)
+    #[arg(long, env = "DEV")]
+    pub dev: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind: SocketAddr,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub cache_time: Duration,
+    pub from_year: i32,
+    pub channel_id: u64,
+    pub channel_name: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub timezone: Tz,
+    /// If not set, the embedded templates are used (if enabled).
+    pub template_dir: Option<PathBuf>,
+    /// If not set, the embedded static files are used (if enabled).
+    pub static_dir: Option<PathBuf>,
+    pub variety_threshold: f64,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub headline_metric: Metric,
+    pub short_stream_minutes: i64,
+    pub breaks_file: Option<PathBuf>,
+    pub schedule: Option<String>,
+    /// Reads the templates from the `template_dir` again on every render.
+    pub dev: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([127, 0, 0, 1], 8934)),
+            cache_time: DEFAULT_CACHE_TIME,
+            from_year: DEFAULT_FROM_YEAR,
+            channel_id: DEFAULT_CHANNEL_ID,
+            channel_name: DEFAULT_CHANNEL_NAME.to_owned(),
+            timezone: DEFAULT_TIMEZONE,
+            template_dir: None,
+            static_dir: None,
+            variety_threshold: DEFAULT_VARIETY_THRESHOLD,
+            headline_metric: Metric::default(),
+            short_stream_minutes: ModelOptions::default().short_stream_min,
+            breaks_file: None,
+            schedule: None,
+            dev: false,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file and applies the overrides from the command line and environment.
+    pub fn load(cli: Cli) -> Result<Self> {
+        let mut config = match &cli.config {
+            Some(path) => Self::read(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::read(DEFAULT_CONFIG_FILE.as_ref())?
+            }
+            None => Self::default(),
+        };
+        config.apply(cli.overrides);
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the config file {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    fn apply(&mut self, overrides: Overrides) {
+        let Overrides {
+            bind,
+            cache_time,
+            from_year,
+            channel_id,
+            channel_name,
+            timezone,
+            template_dir,
+            static_dir,
+            variety_threshold,
+            headline_metric,
+            short_stream_minutes,
+            breaks_file,
+            schedule,
+            dev,
+        } = overrides;
+        self.bind = bind.unwrap_or(self.bind);
+        self.cache_time = cache_time.unwrap_or(self.cache_time);
+        self.from_year = from_year.unwrap_or(self.from_year);
+        self.channel_id = channel_id.unwrap_or(self.channel_id);
+        if let Some(channel_name) = channel_name {
+            self.channel_name = channel_name;
+        }
+        self.timezone = timezone.unwrap_or(self.timezone);
+        if template_dir.is_some() {
+            self.template_dir = template_dir;
+        }
+        if static_dir.is_some() {
+            self.static_dir = static_dir;
+        }
+        self.variety_threshold = variety_threshold.unwrap_or(self.variety_threshold);
+        self.headline_metric = headline_metric.unwrap_or(self.headline_metric);
+        self.short_stream_minutes = short_stream_minutes.unwrap_or(self.short_stream_minutes);
+        if breaks_file.is_some() {
+            self.breaks_file = breaks_file;
+        }
+        if schedule.is_some() {
+            self.schedule = schedule;
+        }
+        self.dev |= dev;
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.variety_threshold > 0.0 && self.variety_threshold < 1.0) {
+            bail!(
+                "variety_threshold must be between 0 and 1 (exclusive), got {}",
+                self.variety_threshold
+            );
+        }
+        let current_year = Utc::now().year();
+        if self.from_year > current_year {
+            bail!(
+                "from_year must not be in the future, got {} (it's {current_year})",
+                self.from_year
+            );
+        }
+        if self.cache_time.is_zero() {
+            bail!("cache_time must be longer than zero");
+        }
+        if self.short_stream_minutes < 0 {
+            bail!(
+                "short_stream_minutes must not be negative, got {}",
+                self.short_stream_minutes
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that the template and static directories exist.
+    /// Only needed by the commands that render pages.
+    pub fn validate_assets(&self) -> Result<()> {
+        for (name, assets) in [
+            ("template_dir", self.templates()),
+            ("static_dir", self.static_files()),
+        ] {
+            match assets {
+                Assets::Disk(dir) if !dir.is_dir() => {
+                    bail!("{name} {} isn't a directory", dir.display())
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn model_options(&self) -> Result<ModelOptions> {
+        Ok(ModelOptions {
+            headline: self.headline_metric,
+            short_stream_min: self.short_stream_minutes,
+            variety_threshold: self.variety_threshold,
+            breaks: Arc::new(match &self.breaks_file {
+                Some(path) => Breaks::load(path)?,
+                None => Breaks::default(),
+            }),
+            schedule: Arc::new(match &self.schedule {
+                Some(schedule) => schedule
+                    .parse::<DeclaredSchedule>()
+                    .context("Invalid schedule")?,
+                None => DeclaredSchedule::default(),
+            }),
+            timezone: self.timezone,
+        })
+    }
+
+    pub fn site(&self) -> Site {
+        Site {
+            channel_name: self.channel_name.clone(),
+            cache_time: humantime::format_duration(self.cache_time).to_string(),
+            static_site: false,
+        }
+    }
+
+    pub fn templates(&self) -> Assets {
+        assets(&self.template_dir, "templates")
+    }
+
+    pub fn static_files(&self) -> Assets {
+        assets(&self.static_dir, "static")
+    }
+
+    pub fn fetch_options(&self) -> FetchOptions {
+        FetchOptions {
+            channel_id: self.channel_id,
+            from_year: self.from_year,
+            cache_time: self.cache_time,
+        }
+    }
+}
+
+/// Directories set in the config take precedence over the embedded files.
+#[cfg_attr(feature = "embed", allow(unused_variables))]
+fn assets(dir: &Option<PathBuf>, default: &str) -> Assets {
+    match dir {
+        Some(dir) => Assets::Disk(dir.clone()),
+        #[cfg(feature = "embed")]
+        None => Assets::Embedded,
+        #[cfg(not(feature = "embed"))]
+        None => Assets::Disk(default.into()),
+    }
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    humantime::parse_duration(&value)
+        .map_err(|e| serde::de::Error::custom(format!("invalid duration '{value}': {e}")))
+}
+
+fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Config, Overrides};
+    use std::time::Duration;
+
+    #[test]
+    fn parses_config() {
+        let mut config = toml::from_str::<Config>(
+            "cache_time = \"5m\"\nheadline_metric = \"gini\"\nvariety_threshold = 0.25\ntimezone = \"America/New_York\"",
+        )
+        .unwrap();
+        assert_eq!(config.cache_time, Duration::from_secs(5 * 60));
+        assert_eq!(config.from_year, 2018);
+        assert_eq!(config.timezone, chrono_tz::America::New_York);
+        config.apply(Overrides {
+            from_year: Some(2020),
+            ..Default::default()
+        });
+        assert_eq!(config.from_year, 2020);
+        assert!(config.validate().is_ok());
+
+        config.variety_threshold = 1.0;
+        assert!(config.validate().is_err());
+        assert!(toml::from_str::<Config>("cache_time = \"soon\"").is_err());
+        assert!(toml::from_str::<Config>("threshold = 0.3").is_err());
+        assert!(toml::from_str::<Config>("timezone = \"Mars/Olympus\"").is_err());
+    }
+}
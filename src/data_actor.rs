@@ -1,62 +1,410 @@
 use crate::{
-    model::{StreamerModel, Year},
+    categories,
+    model::{GameModel, ModelOptions, StreamerModel, Surrounding},
+    period::Period,
     sullygnome::{self, GamesResponse, StreamsResponse},
 };
 use actix::{
     fut::ready, Actor, ActorFuture, ActorFutureExt, AsyncContext, Context, Handler, Message,
-    ResponseActFuture, WrapFuture,
+    MessageResult, ResponseActFuture, WrapFuture,
 };
 use anyhow::anyhow;
-use chrono::{Datelike, Utc};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Utc};
 use futures::future;
 use std::{
     collections::HashMap,
+    fmt,
     sync::Arc,
     time::{Duration, Instant},
 };
 
-const CACHE_TIME: Duration = Duration::from_secs(10 * 60);
-const FROM_YEAR: i32 = 2018;
+pub const DEFAULT_CACHE_TIME: Duration = Duration::from_secs(10 * 60);
+pub const DEFAULT_FROM_YEAR: i32 = 2018;
+/// Rolling windows (in days) shown next to the year-to-date numbers.
+pub const ROLLING_WINDOWS: [u32; 3] = [30, 90, 365];
+
+/// Which data is fetched and how long it's cached.
+#[derive(Debug, Copy, Clone)]
+pub struct FetchOptions {
+    /// SullyGnome id of the channel.
+    pub channel_id: u64,
+    /// The first year that's tracked.
+    pub from_year: i32,
+    /// How long the current year is cached.
+    pub cache_time: Duration,
+}
+
+/// The requested data isn't tracked (e.g. a year before `from_year`).
+#[derive(Debug)]
+pub struct NotTracked(String);
+
+impl fmt::Display for NotTracked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotTracked {}
+
+/// The previous years are requested before they're loaded on startup.
+#[derive(Debug)]
+pub struct Loading;
+
+impl fmt::Display for Loading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("The previous years are still loading")
+    }
+}
+
+impl std::error::Error for Loading {}
+
+/// A previous year couldn't be fetched from SullyGnome.
+#[derive(Debug)]
+pub struct FetchFailed(i32);
+
+impl fmt::Display for FetchFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} couldn't be fetched", self.0)
+    }
+}
+
+impl std::error::Error for FetchFailed {}
 
 pub struct DataActor {
     current_year: Option<(Instant, Arc<StreamerModel>)>,
+    /// Models of the [`ROLLING_WINDOWS`], built from the cached `current_year`.
+    rolling: HashMap<u32, Arc<StreamerModel>>,
+    /// All years merged, built from the cached `current_year`.
+    all_time: Option<Arc<StreamerModel>>,
     prev_years: HashMap<i32, Arc<StreamerModel>>,
+    /// Start of the stream the newest previous year was enclosed with.
+    last_year_after: Option<DateTime<Utc>>,
     years_n: Arc<Vec<i32>>,
     current_year_n: i32,
+    /// Whether the previous years are loaded yet.
+    loaded: bool,
+    /// Previous years that couldn't be fetched, retried when they're requested.
+    failed_years: Vec<i32>,
+    /// When the previous years were last requested from SullyGnome.
+    last_load: Option<Instant>,
+    /// Don't handle any request until the previous years are loaded.
+    wait_for_load: bool,
+    options: ModelOptions,
+    fetch: FetchOptions,
 }
 
-impl Default for DataActor {
-    fn default() -> Self {
+impl DataActor {
+    pub fn new(options: ModelOptions, fetch: FetchOptions) -> Self {
         Self {
             current_year: None,
+            rolling: HashMap::new(),
+            all_time: None,
             prev_years: HashMap::new(),
+            last_year_after: None,
             current_year_n: Utc::now().year(),
             years_n: Arc::new(Vec::new()),
+            loaded: false,
+            failed_years: Vec::new(),
+            last_load: None,
+            wait_for_load: false,
+            options,
+            fetch,
         }
     }
-}
 
-impl DataActor {
+    /// Requests are only handled once the previous years are loaded,
+    /// instead of failing while they're loading.
+    pub fn wait_for_load(mut self) -> Self {
+        self.wait_for_load = true;
+        self
+    }
+
+    /// A year is only missing for good once the previous years are loaded
+    /// and if it wasn't missing because SullyGnome was unavailable.
+    fn missing_year(&self, year: i32) -> anyhow::Error {
+        if !self.loaded {
+            Loading.into()
+        } else if self.failed_years.contains(&year) {
+            FetchFailed(year).into()
+        } else {
+            NotTracked(format!("{year} isn't tracked")).into()
+        }
+    }
+
+    /// Failed years are fetched again at most once per `cache_time`.
+    fn should_retry(&self, year: i32) -> bool {
+        self.failed_years.contains(&year)
+            && self
+                .last_load
+                .is_none_or(|at| at.elapsed() > self.fetch.cache_time)
+    }
+
     fn put_current_response(
         &mut self,
         response: anyhow::Result<(sullygnome::GamesResponse, sullygnome::StreamsResponse)>,
     ) -> <GetData as Message>::Result {
         let (games, streams) = response?;
-        let model = Arc::new(StreamerModel::create(Year::Current, games, streams)?);
+        let surrounding = Surrounding {
+            before: self
+                .prev_years
+                .get(&(self.current_year_n - 1))
+                .and_then(|model| model.streams.first().cloned()),
+            after: None,
+        };
+        let mut model = StreamerModel::from_responses(
+            Period::CurrentYear,
+            &self.options,
+            games,
+            streams,
+            surrounding,
+        )?;
+        model.timeline.mark_new(&categories::played(
+            self.prev_years.values().flat_map(|model| &model.streams),
+        ));
+        let model = Arc::new(model);
         self.current_year = Some((Instant::now(), Arc::clone(&model)));
+        self.enclose_last_year();
+        self.clear_derived();
         Ok((model, self.years_n.clone()))
     }
 
-    fn put_last_response(&mut self, response: impl Iterator<Item = (i32, Arc<StreamerModel>)>) {
-        self.prev_years = HashMap::from_iter(response);
+    /// All tracked years including the current one, from oldest to newest.
+    fn all_years(&self) -> <GetAllYears as Message>::Result {
+        let mut years = self.prev_years.values().cloned().collect::<Vec<_>>();
+        years.extend(self.current_year.as_ref().map(|(_, model)| model.clone()));
+        years.sort_by_key(|model| model.year);
+        Ok((years, self.years_n.clone()))
+    }
+
+    /// Models derived from the current year have to be rebuilt after it's fetched again.
+    fn clear_derived(&mut self) {
+        self.rolling.clear();
+        self.all_time = None;
+    }
+
+    /// Like [`Self::create_all_time`], but cached until the current year is fetched again.
+    fn get_all_time(&mut self) -> <GetAllTime as Message>::Result {
+        if let Some(model) = &self.all_time {
+            return Ok((model.clone(), self.years_n.clone()));
+        }
+        let (model, years) = self.create_all_time()?;
+        self.all_time = Some(model.clone());
+        Ok((model, years))
+    }
+
+    /// Merges all tracked years into one model.
+    fn create_all_time(&self) -> <GetAllTime as Message>::Result {
+        let (models, years_n) = self.all_years()?;
+        let since = models
+            .first()
+            .map(|model| model.year)
+            .ok_or_else(|| anyhow!("No year is tracked"))?;
+        let games = GameModel::merge(models.iter().map(|model| model.games.as_slice()));
+        // the models are sorted from oldest to newest, the streams from newest to oldest
+        let streams = models
+            .iter()
+            .rev()
+            .flat_map(|model| model.streams.iter().cloned())
+            .collect();
+        let mut model = StreamerModel::create(
+            Period::AllTime { since },
+            &self.options,
+            games,
+            streams,
+            Surrounding::default(),
+        )?;
+        if let Some(fetched_at) = models.iter().map(|model| model.fetched_at).max() {
+            model.fetched_at = fetched_at;
+        }
+        Ok((Arc::new(model), years_n))
+    }
+
+    /// Like [`Self::create_period`], but the [`ROLLING_WINDOWS`] are cached
+    /// until the current year is fetched again.
+    fn get_period(&mut self, period: Period) -> <GetData as Message>::Result {
+        let days = match period {
+            Period::LastDays(days) if ROLLING_WINDOWS.contains(&days) => days,
+            period => return self.create_period(period),
+        };
+        if let Some(model) = self.rolling.get(&days) {
+            return Ok((model.clone(), self.years_n.clone()));
+        }
+        let (model, years) = self.create_period(period)?;
+        self.rolling.insert(days, model.clone());
+        Ok((model, years))
+    }
+
+    /// Builds a model for a period that isn't a calendar year
+    /// from the streams of all years it touches.
+    fn create_period(&self, period: Period) -> <GetData as Message>::Result {
+        let (start, end) = period.bounds();
+        let clamp_end = end - ChronoDuration::seconds(1);
+        let mut streams = Vec::new();
+        let mut fetched_at = DateTime::<Utc>::MIN_UTC;
+        for year in period.years().rev() {
+            let model = if year == self.current_year_n {
+                self.current_year.as_ref().map(|(_, model)| model)
+            } else {
+                self.prev_years.get(&year)
+            }
+            .ok_or_else(|| self.missing_year(year))?;
+            fetched_at = fetched_at.max(model.fetched_at);
+            streams.extend(
+                model
+                    .streams
+                    .iter()
+                    .filter(|stream| stream.overlaps(&start, &end))
+                    .map(|stream| {
+                        let mut stream = stream.clone();
+                        stream.clamp(&start, &clamp_end);
+                        stream
+                    }),
+            );
+        }
+        let surrounding = Surrounding::find(
+            period,
+            self.prev_years
+                .values()
+                .chain(self.current_year.as_ref().map(|(_, model)| model))
+                .flat_map(|model| model.streams.iter()),
+        );
+        let mut model = StreamerModel::from_streams(period, &self.options, streams, surrounding)?;
+        model.timeline.mark_new(&categories::played(
+            self.prev_years
+                .values()
+                .chain(self.current_year.as_ref().map(|(_, model)| model))
+                .flat_map(|model| &model.streams)
+                .filter(|stream| stream.start_date_time < start),
+        ));
+        model.fetched_at = fetched_at;
+        Ok((Arc::new(model), self.years_n.clone()))
+    }
+
+    fn put_last_response(
+        &mut self,
+        response: impl Iterator<Item = (i32, GamesResponse, StreamsResponse)>,
+    ) {
+        let mut responses = response.collect::<Vec<_>>();
+        responses.sort_by_key(|(year, ..)| *year);
+        // streams are sorted from newest to oldest
+        let surroundings = (0..responses.len())
+            .map(|i| {
+                let year = responses[i].0;
+                Surrounding {
+                    before: i
+                        .checked_sub(1)
+                        .map(|prev| &responses[prev])
+                        .filter(|(prev, ..)| *prev == year - 1)
+                        .and_then(|(_, _, streams)| streams.data.first().cloned()),
+                    after: match responses.get(i + 1) {
+                        Some((next, _, streams)) if *next == year + 1 => {
+                            streams.data.last().cloned()
+                        }
+                        // the newest year is enclosed once the current year is fetched
+                        _ => None,
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        let options = &self.options;
+        let mut models = responses
+            .into_iter()
+            .zip(surroundings)
+            .filter_map(|((year, games, streams), surrounding)| {
+                StreamerModel::from_responses(
+                    Period::Year(year),
+                    options,
+                    games,
+                    streams,
+                    surrounding,
+                )
+                .ok()
+                .map(|model| (year, model))
+            })
+            .collect::<Vec<_>>();
+        // the models are sorted from oldest to newest
+        for i in 0..models.len() {
+            let (earlier, rest) = models.split_at_mut(i);
+            rest[0].1.timeline.mark_new(&categories::played(
+                earlier.iter().flat_map(|(_, model)| &model.streams),
+            ));
+        }
+        self.prev_years = models
+            .into_iter()
+            .map(|(year, model)| (year, Arc::new(model)))
+            .collect();
         let mut vec = Vec::from_iter(self.prev_years.keys().copied());
         vec.sort_by(|a, b| b.cmp(a));
         self.years_n = Arc::new(vec);
+        self.loaded = true;
+        // the current year is fetched again to mark its new categories
+        // and to enclose the newest previous year
+        self.current_year = None;
+        self.last_year_after = None;
+        self.clear_derived();
+    }
+
+    /// Rebuilds the newest previous year with the first stream of the current year,
+    /// so a ditch over New Year's isn't cut off at the end of the year.
+    fn enclose_last_year(&mut self) {
+        let Some((_, current)) = &self.current_year else {
+            return;
+        };
+        // streams are sorted from newest to oldest
+        let after = current.streams.last().cloned();
+        let after_start = after.as_ref().map(|stream| stream.start_date_time);
+        if after_start == self.last_year_after {
+            return;
+        }
+        let year = self.current_year_n - 1;
+        let Some(last_year) = self.prev_years.get(&year) else {
+            return;
+        };
+        let surrounding = Surrounding {
+            before: self
+                .prev_years
+                .get(&(year - 1))
+                .and_then(|model| model.streams.first().cloned()),
+            after,
+        };
+        let mut model = match StreamerModel::create(
+            Period::Year(year),
+            &self.options,
+            last_year.games.clone(),
+            last_year.streams.clone(),
+            surrounding,
+        ) {
+            Ok(model) => model,
+            Err(e) => {
+                eprintln!("Failed to rebuild {year}: {e:#}");
+                return;
+            }
+        };
+        model.timeline.mark_new(&categories::played(
+            self.prev_years
+                .iter()
+                .filter(|(prev, _)| **prev < year)
+                .flat_map(|(_, model)| &model.streams),
+        ));
+        self.prev_years.insert(year, Arc::new(model));
+        self.last_year_after = after_start;
+    }
+
+    fn fetch_current_year(
+        &self,
+    ) -> impl ActorFuture<Self, Output = anyhow::Result<(GamesResponse, StreamsResponse)>> {
+        let current_year = Utc::now().year();
+        let channel_id = self.fetch.channel_id;
+        future::try_join(
+            sullygnome::get_all_of::<GamesResponse>(channel_id, current_year),
+            sullygnome::get_all_of::<StreamsResponse>(channel_id, current_year),
+        )
+        .into_actor(self)
     }
 
     fn try_get_cached(&self) -> Option<<GetData as Message>::Result> {
         let (instant, model) = &self.current_year.as_ref()?;
-        if Instant::now() - *instant > CACHE_TIME {
+        if Instant::now() - *instant > self.fetch.cache_time {
             None
         } else {
             Some(Ok((model.clone(), self.years_n.clone())))
@@ -64,27 +412,41 @@ impl DataActor {
     }
 
     fn get_last_year(
-        &self,
+        &mut self,
         year: i32,
     ) -> impl ActorFuture<Self, Output = <GetData as Message>::Result> {
-        let f = (FROM_YEAR.min(self.current_year_n)..self.current_year_n).map(|year| async move {
-            let (games, streams) = future::try_join(
-                sullygnome::get_all_of::<GamesResponse>(year),
-                sullygnome::get_all_of::<StreamsResponse>(year),
+        self.last_load = Some(Instant::now());
+        let channel_id = self.fetch.channel_id;
+        let from_year = self.fetch.from_year.min(self.current_year_n);
+        let f = (from_year..self.current_year_n).map(|year| async move {
+            let response = future::try_join(
+                sullygnome::get_all_of::<GamesResponse>(channel_id, year),
+                sullygnome::get_all_of::<StreamsResponse>(channel_id, year),
             )
-            .await?;
-            StreamerModel::create(Year::Last(year), games, streams).map(|m| (year, Arc::new(m)))
+            .await;
+            (year, response)
         });
 
         future::join_all(f)
             .into_actor(self)
             .map(move |res, this, _| {
-                this.put_last_response(res.into_iter().filter_map(Result::ok));
+                let mut responses = Vec::new();
+                this.failed_years.clear();
+                for (year, response) in res {
+                    match response {
+                        Ok((games, streams)) => responses.push((year, games, streams)),
+                        Err(e) => {
+                            eprintln!("Failed to fetch {year}: {e:#}");
+                            this.failed_years.push(year);
+                        }
+                    }
+                }
+                this.put_last_response(responses.into_iter());
                 this.prev_years
                     .get(&year)
                     .cloned()
                     .map(|y| (y, this.years_n.clone()))
-                    .ok_or_else(|| anyhow!("No such year exists"))
+                    .ok_or_else(|| this.missing_year(year))
             })
     }
 }
@@ -93,13 +455,19 @@ impl Actor for DataActor {
     type Context = Context<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
-        ctx.spawn(self.get_last_year(0).map(|_, this, _| {
+        let load = self.get_last_year(0).map(|_, this, _| {
             eprintln!("Loaded {} last year(s)", this.prev_years.len());
-        }));
+        });
+        match self.wait_for_load {
+            true => ctx.wait(load),
+            false => {
+                ctx.spawn(load);
+            }
+        }
     }
 }
 
-pub struct GetData(pub Year);
+pub struct GetData(pub Period);
 
 impl Message for GetData {
     type Result = anyhow::Result<(Arc<StreamerModel>, Arc<Vec<i32>>)>;
@@ -108,38 +476,202 @@ impl Message for GetData {
 impl Handler<GetData> for DataActor {
     type Result = ResponseActFuture<Self, <GetData as Message>::Result>;
 
-    fn handle(&mut self, GetData(year): GetData, _: &mut Self::Context) -> Self::Result {
-        match year {
-            Year::Current => match self.try_get_cached() {
+    fn handle(&mut self, GetData(period): GetData, _: &mut Self::Context) -> Self::Result {
+        match period {
+            Period::CurrentYear => match self.try_get_cached() {
                 Some(cached) => Box::pin(ready(cached)),
-                None => {
-                    let current_year = Utc::now().year();
-                    Box::pin(
-                        future::try_join(
-                            sullygnome::get_all_of::<GamesResponse>(current_year),
-                            sullygnome::get_all_of::<StreamsResponse>(current_year),
-                        )
-                        .into_actor(self)
+                None => Box::pin(
+                    self.fetch_current_year()
                         .map(|res, this, _| this.put_current_response(res)),
-                    )
-                }
+                ),
             },
-            Year::Last(year) => {
+            Period::Year(year) => {
                 let current_year = Utc::now().year();
-                if self.current_year_n == current_year {
+                if self.current_year_n != current_year {
+                    // the year changed while we were running
+                    self.current_year_n = current_year;
+                    Box::pin(self.get_last_year(year))
+                } else if self.should_retry(year) {
+                    Box::pin(self.get_last_year(year))
+                } else {
                     Box::pin(ready(
                         self.prev_years
                             .get(&year)
                             .cloned()
                             .map(|y| (y, self.years_n.clone()))
-                            .ok_or_else(|| anyhow!("This year isn't tracked")),
+                            .ok_or_else(|| self.missing_year(year)),
                     ))
+                }
+            }
+            period => {
+                if !period.years().contains(&self.current_year_n) || self.try_get_cached().is_some()
+                {
+                    Box::pin(ready(self.get_period(period)))
                 } else {
-                    // the year changed while we were running
-                    self.current_year_n = current_year;
-                    Box::pin(self.get_last_year(year))
+                    Box::pin(self.fetch_current_year().map(move |res, this, _| {
+                        this.put_current_response(res)?;
+                        this.get_period(period)
+                    }))
                 }
             }
         }
     }
 }
+
+pub struct GetAllYears;
+
+impl Message for GetAllYears {
+    type Result = anyhow::Result<(Vec<Arc<StreamerModel>>, Arc<Vec<i32>>)>;
+}
+
+impl Handler<GetAllYears> for DataActor {
+    type Result = ResponseActFuture<Self, <GetAllYears as Message>::Result>;
+
+    fn handle(&mut self, _: GetAllYears, _: &mut Self::Context) -> Self::Result {
+        if !self.loaded {
+            return Box::pin(ready(Err(Loading.into())));
+        }
+        match self.try_get_cached() {
+            Some(_) => Box::pin(ready(self.all_years())),
+            None => Box::pin(self.fetch_current_year().map(|res, this, _| {
+                this.put_current_response(res)?;
+                this.all_years()
+            })),
+        }
+    }
+}
+
+/// The tracked years before the current one, without fetching anything.
+pub struct GetYears;
+
+impl Message for GetYears {
+    type Result = Arc<Vec<i32>>;
+}
+
+impl Handler<GetYears> for DataActor {
+    type Result = MessageResult<GetYears>;
+
+    fn handle(&mut self, _: GetYears, _: &mut Self::Context) -> Self::Result {
+        MessageResult(self.years_n.clone())
+    }
+}
+
+pub struct GetAllTime;
+
+impl Message for GetAllTime {
+    type Result = anyhow::Result<(Arc<StreamerModel>, Arc<Vec<i32>>)>;
+}
+
+impl Handler<GetAllTime> for DataActor {
+    type Result = ResponseActFuture<Self, <GetAllTime as Message>::Result>;
+
+    fn handle(&mut self, _: GetAllTime, _: &mut Self::Context) -> Self::Result {
+        if !self.loaded {
+            return Box::pin(ready(Err(Loading.into())));
+        }
+        match self.try_get_cached() {
+            Some(_) => Box::pin(ready(self.get_all_time())),
+            None => Box::pin(self.fetch_current_year().map(|res, this, _| {
+                this.put_current_response(res)?;
+                this.get_all_time()
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        data_actor::{
+            DataActor, FetchFailed, FetchOptions, Loading, NotTracked, DEFAULT_CACHE_TIME,
+        },
+        model::{ModelOptions, StreamerModel, Surrounding},
+        period::Period,
+        streamcounter::LongestDitch,
+        sullygnome::{StreamData, StreamGame},
+    };
+    use chrono::{TimeZone, Utc};
+    use std::{sync::Arc, time::Instant};
+
+    fn actor() -> DataActor {
+        DataActor::new(
+            ModelOptions::default(),
+            FetchOptions {
+                channel_id: 0,
+                from_year: 2018,
+                cache_time: DEFAULT_CACHE_TIME,
+            },
+        )
+    }
+
+    #[test]
+    fn reports_why_a_year_is_missing() {
+        let mut actor = actor();
+        assert!(actor.missing_year(2020).is::<Loading>());
+        actor.loaded = true;
+        actor.failed_years = vec![2020];
+        assert!(actor.missing_year(2020).is::<FetchFailed>());
+        assert!(actor.missing_year(2010).is::<NotTracked>());
+
+        assert!(actor.should_retry(2020));
+        assert!(!actor.should_retry(2010));
+        actor.last_load = Some(Instant::now());
+        assert!(!actor.should_retry(2020));
+    }
+
+    #[test]
+    fn encloses_last_year_with_current_year() {
+        let stream = |year: i32, month: u32, day: u32| StreamData {
+            start_date_time: Utc.with_ymd_and_hms(year, month, day, 18, 0, 0).unwrap(),
+            length: 60,
+            games: vec![StreamGame {
+                category: "Minecraft".to_owned(),
+                category_image: String::new(),
+            }],
+            clamped: false,
+        };
+        let mut actor = actor();
+        let last_year = actor.current_year_n - 1;
+        let model = |period, streams| {
+            Arc::new(
+                StreamerModel::create(
+                    period,
+                    &ModelOptions::default(),
+                    Vec::new(),
+                    streams,
+                    Surrounding::default(),
+                )
+                .unwrap(),
+            )
+        };
+        actor.prev_years.insert(
+            last_year,
+            model(
+                Period::Year(last_year),
+                vec![stream(last_year, 12, 20), stream(last_year, 12, 10)],
+            ),
+        );
+        actor.enclose_last_year();
+        let LongestDitch::Past { from, .. } = &actor.prev_years[&last_year].longest_ditch else {
+            panic!("expected a past ditch");
+        };
+        assert_eq!(
+            from.date_naive(),
+            stream(last_year, 12, 10).start_date_time.date_naive()
+        );
+
+        actor.current_year = Some((
+            Instant::now(),
+            model(Period::CurrentYear, vec![stream(last_year + 1, 1, 5)]),
+        ));
+        actor.enclose_last_year();
+        let LongestDitch::Past { from, .. } = &actor.prev_years[&last_year].longest_ditch else {
+            panic!("expected a past ditch");
+        };
+        // the ditch from December 20th into January isn't cut off at the end of the year
+        assert_eq!(
+            from.date_naive(),
+            stream(last_year, 12, 20).start_date_time.date_naive()
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crate::model::GameModel;
+use anyhow::{bail, Error};
+use serde::Serialize;
+use std::{cmp::Reverse, str::FromStr};
+
+/// The metric used for the headline "We are X% variety".
+#[derive(Serialize, Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Metric {
+    /// Everything but the most played game.
+    #[default]
+    TopGame,
+    /// Everything but the three most played games.
+    TopThree,
+    /// Normalized Shannon entropy of the time per game.
+    Evenness,
+    /// One minus the Gini coefficient of the time per game.
+    Gini,
+}
+
+impl Metric {
+    pub fn description(self) -> &'static str {
+        match self {
+            Metric::TopGame => "Variety is everything but the most played game.",
+            Metric::TopThree => "Variety is everything but the three most played games.",
+            Metric::Evenness => "Variety is how evenly the time is spread across games.",
+            Metric::Gini => "Variety is one minus the Gini coefficient of the time per game.",
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "top-game" => Metric::TopGame,
+            "top-three" => Metric::TopThree,
+            "evenness" => Metric::Evenness,
+            "gini" => Metric::Gini,
+            _ => {
+                bail!("Unknown metric '{s}', expected one of: top-game, top-three, evenness, gini")
+            }
+        })
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Diversity {
+    /// Number of distinct categories with any stream time.
+    pub categories: usize,
+    /// Shannon entropy (in nats) of the share per game.
+    pub entropy: f64,
+    /// `exp(entropy)` - the number of equally played games with the same entropy.
+    pub effective_games: f64,
+    pub gini: f64,
+    pub top_share: f64,
+    pub top3_share: f64,
+}
+
+impl Diversity {
+    pub fn calculate(games: &[GameModel]) -> Self {
+        let mut minutes = games
+            .iter()
+            .map(|g| g.time_streamed_min)
+            .filter(|min| *min > 0)
+            .collect::<Vec<_>>();
+        minutes.sort_by_key(|min| Reverse(*min));
+        let total = minutes.iter().sum::<u64>();
+        if total == 0 {
+            return Self {
+                categories: 0,
+                entropy: 0.0,
+                effective_games: 0.0,
+                gini: 0.0,
+                top_share: 0.0,
+                top3_share: 0.0,
+            };
+        }
+
+        let shares = minutes
+            .iter()
+            .map(|min| *min as f64 / total as f64)
+            .collect::<Vec<_>>();
+        let entropy = -shares.iter().map(|p| p * p.ln()).sum::<f64>();
+
+        // G = (n + 1 - 2 * sum((n + 1 - i) * p_i)) / n with p sorted ascending and i starting at 1
+        let n = shares.len() as f64;
+        let weighted = shares
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, p)| (n - i as f64) * p)
+            .sum::<f64>();
+        let gini = ((n + 1.0 - 2.0 * weighted) / n).max(0.0);
+
+        Self {
+            categories: shares.len(),
+            entropy,
+            effective_games: entropy.exp(),
+            gini,
+            top_share: shares[0],
+            top3_share: shares.iter().take(3).sum(),
+        }
+    }
+
+    /// The share of variety according to `metric` (0 to 1).
+    pub fn variety(&self, metric: Metric) -> f64 {
+        // a single game is never variety, no matter how evenly it's distributed
+        if self.categories <= 1 {
+            return 0.0;
+        }
+        match metric {
+            Metric::TopGame => 1.0 - self.top_share,
+            Metric::TopThree => 1.0 - self.top3_share,
+            Metric::Evenness => self.entropy / (self.categories as f64).ln(),
+            Metric::Gini => 1.0 - self.gini,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        diversity::{Diversity, Metric},
+        model::GameModel,
+    };
+
+    fn game(time_streamed_min: u64) -> GameModel {
+        GameModel {
+            time_streamed_min,
+            category: String::new(),
+            category_image: String::new(),
+        }
+    }
+
+    #[test]
+    fn metrics() {
+        let even = Diversity::calculate(&[game(10), game(10), game(10), game(10)]);
+        assert_eq!(even.categories, 4);
+        assert!((even.effective_games - 4.0).abs() < 1e-9);
+        assert!(even.gini.abs() < 1e-9);
+        assert!((even.variety(Metric::Evenness) - 1.0).abs() < 1e-9);
+
+        let skewed = Diversity::calculate(&[game(69), game(31), game(0)]);
+        assert_eq!(skewed.categories, 2);
+        assert!((skewed.variety(Metric::TopGame) - 0.31).abs() < 1e-9);
+        assert!((skewed.gini - 0.19).abs() < 1e-9);
+        assert_eq!(skewed.variety(Metric::TopThree), 0.0);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    data_actor::{FetchFailed, GetYears, Loading, NotTracked},
+    templates::{Site, Templates},
+};
+use actix::{MailboxError, Recipient};
+use actix_web::{
+    dev::ServiceResponse,
+    error,
+    http::{
+        header::{self, HeaderValue},
+        StatusCode,
+    },
+    middleware::ErrorHandlerResponse,
+    web, HttpResponse,
+};
+use serde::Serialize;
+
+/// Sent in `Retry-After` if SullyGnome or the data actor is unavailable.
+const RETRY_AFTER_SECS: u32 = 60;
+
+#[derive(Serialize)]
+struct ErrorContext<'a> {
+    site: &'a Site,
+    years: &'a [i32],
+    child: &'static str,
+    page: ErrorPage,
+}
+
+#[derive(Serialize)]
+struct ErrorPage {
+    status: u16,
+    reason: &'static str,
+    message: String,
+}
+
+/// Maps errors from the data actor to 404 (not tracked),
+/// 503 (still loading or SullyGnome is down) or 500.
+pub fn data_error(e: anyhow::Error) -> actix_web::Error {
+    if e.is::<NotTracked>() {
+        error::ErrorNotFound(e)
+    } else if e.is::<Loading>() {
+        unavailable(format!("{e}, try again later"))
+    } else if e.is::<FetchFailed>() || e.chain().any(|cause| cause.is::<reqwest::Error>()) {
+        eprintln!("Failed to fetch data: {e:#}");
+        unavailable("SullyGnome is unavailable, try again later")
+    } else {
+        error::ErrorInternalServerError(e)
+    }
+}
+
+/// The data actor is busy or stopped.
+pub fn mailbox_error(e: MailboxError) -> actix_web::Error {
+    unavailable(format!("The data is unavailable ({e}), try again later"))
+}
+
+fn unavailable(message: impl Into<String>) -> actix_web::Error {
+    let message = message.into();
+    error::InternalError::from_response(
+        message.clone(),
+        HttpResponse::ServiceUnavailable()
+            .insert_header((header::RETRY_AFTER, RETRY_AFTER_SECS))
+            .body(message),
+    )
+    .into()
+}
+
+/// Renders errors with the `error` template, except for the API.
+/// The status and headers (e.g. `Retry-After`) of the response are kept.
+pub fn render_error_page<B: 'static>(
+    res: ServiceResponse<B>,
+) -> actix_web::Result<ErrorHandlerResponse<B>> {
+    if res.request().path().starts_with("/api/") {
+        return Ok(ErrorHandlerResponse::Response(res.map_into_left_body()));
+    }
+    Ok(ErrorHandlerResponse::Future(Box::pin(async move {
+        let (req, res) = res.into_parts();
+        let Some(templates) = req.app_data::<web::Data<Templates>>().cloned() else {
+            return Ok(ServiceResponse::new(req, res.map_into_left_body()));
+        };
+        let years = match req.app_data::<web::Data<Recipient<GetYears>>>() {
+            Some(actor) => actor.send(GetYears).await.unwrap_or_default(),
+            None => Default::default(),
+        };
+        let status = res.status();
+        let ctx = ErrorContext {
+            site: templates.site(),
+            years: &years,
+            child: "error",
+            page: ErrorPage {
+                status: status.as_u16(),
+                reason: status.canonical_reason().unwrap_or("Error"),
+                message: match res.error() {
+                    // don't leak internal errors
+                    Some(e) if status == StatusCode::INTERNAL_SERVER_ERROR => {
+                        eprintln!("{} {}: {e}", req.method(), req.path());
+                        "Something went wrong.".to_owned()
+                    }
+                    Some(e) => e.to_string(),
+                    None => String::new(),
+                },
+            },
+        };
+        let res = match templates.render("skeleton", &ctx) {
+            Ok(html) => {
+                let mut res = res.set_body(html);
+                res.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/html; charset=utf-8"),
+                );
+                res.map_into_boxed_body().map_into_right_body()
+            }
+            _ => res.map_into_left_body(),
+        };
+        Ok(ServiceResponse::new(req, res))
+    })))
+}
@@ -0,0 +1,238 @@
+use crate::model::StreamerModel;
+use anyhow::{anyhow, Error, Result};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::Serialize;
+use std::{io::Write, str::FromStr};
+
+/// A file in `/export`, e.g. `streams.csv`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Export {
+    pub data: ExportData,
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExportData {
+    Streams,
+    Days,
+    Games,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamRow<'a> {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    length_min: i64,
+    clamped: bool,
+    games: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DayRow {
+    date: NaiveDate,
+    hours: f32,
+    ditched: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GameRow<'a> {
+    category: &'a str,
+    time_streamed_min: u64,
+    share: f64,
+}
+
+impl FromStr for Export {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (data, format) = s
+            .split_once('.')
+            .ok_or_else(|| anyhow!("Expected a file name like 'streams.csv'"))?;
+        Ok(Self {
+            data: match data {
+                "streams" => ExportData::Streams,
+                "days" => ExportData::Days,
+                "games" => ExportData::Games,
+                _ => return Err(anyhow!("Unknown export '{data}'")),
+            },
+            format: match format {
+                "csv" => ExportFormat::Csv,
+                "jsonl" => ExportFormat::JsonLines,
+                _ => return Err(anyhow!("Unknown format '{format}'")),
+            },
+        })
+    }
+}
+
+impl Export {
+    pub fn write(self, model: &StreamerModel) -> Result<Vec<u8>> {
+        match self.data {
+            ExportData::Streams => {
+                let games = model
+                    .streams
+                    .iter()
+                    .map(|stream| {
+                        stream
+                            .games
+                            .iter()
+                            .map(|game| game.category.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    })
+                    .collect::<Vec<_>>();
+                // oldest first, like the other exports
+                let rows = model
+                    .streams
+                    .iter()
+                    .zip(&games)
+                    .rev()
+                    .map(|(stream, games)| StreamRow {
+                        start: stream.start_date_time,
+                        end: stream.end_date_time(),
+                        length_min: stream.length,
+                        clamped: stream.clamped,
+                        games,
+                    });
+                self.format.write(rows)
+            }
+            ExportData::Days => {
+                let start = model.period_start.date_naive();
+                let rows = model
+                    .days
+                    .iter()
+                    .take(model.days_until_now)
+                    .enumerate()
+                    .map(|(i, hours)| DayRow {
+                        date: start + Days::new(i as u64),
+                        hours: *hours,
+                        ditched: model.is_ditched(i),
+                    });
+                self.format.write(rows)
+            }
+            ExportData::Games => self.format.write(model.games.iter().map(|game| GameRow {
+                category: &game.category,
+                time_streamed_min: game.time_streamed_min,
+                share: if model.total_time_min == 0 {
+                    0.0
+                } else {
+                    game.time_streamed_min as f64 / model.total_time_min as f64
+                },
+            })),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::JsonLines => "application/jsonl; charset=utf-8",
+        }
+    }
+
+    fn write<R: Serialize>(self, rows: impl Iterator<Item = R>) -> Result<Vec<u8>> {
+        match self {
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                for row in rows {
+                    writer.serialize(row)?;
+                }
+                Ok(writer.into_inner().map_err(|e| anyhow!("{e}"))?)
+            }
+            ExportFormat::JsonLines => {
+                let mut out = Vec::new();
+                for row in rows {
+                    serde_json::to_writer(&mut out, &row)?;
+                    out.write_all(b"\n")?;
+                }
+                Ok(out)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        breaks::{Break, Breaks},
+        export::{Export, ExportData, ExportFormat},
+        model::{ModelOptions, StreamerModel, Surrounding},
+        period::Period,
+        sullygnome::StreamData,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn writes_rows() {
+        let export = "days.jsonl".parse::<Export>().unwrap();
+        assert_eq!(export.data, ExportData::Days);
+        assert_eq!(export.format, ExportFormat::JsonLines);
+        assert!("days.xml".parse::<Export>().is_err());
+
+        #[derive(serde::Serialize)]
+        struct Row {
+            name: &'static str,
+            hours: f32,
+        }
+        let rows = [Row {
+            name: "Overwatch, but 2",
+            hours: 1.5,
+        }];
+        assert_eq!(
+            ExportFormat::Csv.write(rows.iter()).unwrap(),
+            b"name,hours\n\"Overwatch, but 2\",1.5\n"
+        );
+        assert_eq!(
+            ExportFormat::JsonLines.write(rows.iter()).unwrap(),
+            b"{\"name\":\"Overwatch, but 2\",\"hours\":1.5}\n"
+        );
+    }
+
+    #[test]
+    fn breaks_are_not_ditched() {
+        let model = StreamerModel::create(
+            Period::Year(2022),
+            &ModelOptions {
+                breaks: Arc::new(Breaks {
+                    breaks: vec![Break {
+                        from: "2022-01-02".parse().unwrap(),
+                        to: "2022-01-03".parse().unwrap(),
+                        reason: None,
+                    }],
+                }),
+                ..Default::default()
+            },
+            Vec::new(),
+            vec![StreamData {
+                start_date_time: "2022-01-01T18:00:00Z".parse().unwrap(),
+                length: 60,
+                games: Vec::new(),
+                clamped: false,
+            }],
+            Surrounding::default(),
+        )
+        .unwrap();
+        let csv = "days.csv".parse::<Export>().unwrap().write(&model).unwrap();
+        let rows = String::from_utf8(csv).unwrap();
+        let rows = rows.lines().take(5).collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                "date,hours,ditched",
+                "2022-01-01,1.0,false",
+                "2022-01-02,0.0,false",
+                "2022-01-03,0.0,false",
+                "2022-01-04,0.0,true",
+            ]
+        );
+    }
+}
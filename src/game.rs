@@ -0,0 +1,131 @@
+use crate::model::StreamerModel;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Everything about a single category, either in one year or across all tracked years.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GamePage {
+    pub category: String,
+    pub category_image: String,
+    pub year: Option<i32>,
+    pub total_min: u64,
+    /// Share of the total time streamed.
+    pub share: f64,
+    pub streams: usize,
+    pub days: usize,
+    pub first_streamed: Option<DateTime<Utc>>,
+    pub last_streamed: Option<DateTime<Utc>>,
+    /// Every tracked year, from oldest to newest.
+    pub years: Vec<GameYear>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GameYear {
+    pub year: i32,
+    pub total_min: u64,
+    pub share: f64,
+    /// Relative to the year with the highest share.
+    pub relative: f64,
+    pub selected: bool,
+}
+
+impl GamePage {
+    /// `models` are all tracked years from oldest to newest.
+    /// Returns `None` if the category wasn't streamed in the selected years.
+    pub fn create(category: &str, year: Option<i32>, models: &[&StreamerModel]) -> Option<Self> {
+        let selected = models
+            .iter()
+            .filter(|model| year.is_none_or(|year| model.year == year))
+            .collect::<Vec<_>>();
+        let category_image = selected
+            .iter()
+            .flat_map(|model| &model.games)
+            .find(|game| game.category == category)?
+            .category_image
+            .clone();
+
+        let total_min = selected
+            .iter()
+            .map(|model| time_streamed(model, category))
+            .sum::<u64>();
+        let all_min = selected
+            .iter()
+            .map(|model| model.total_time_min)
+            .sum::<u64>();
+        let streams = selected
+            .iter()
+            .flat_map(|model| &model.streams)
+            .filter(|stream| stream.games.iter().any(|game| game.category == category))
+            .collect::<Vec<_>>();
+        let spans = selected
+            .iter()
+            .flat_map(|model| &model.timeline.categories)
+            .filter(|span| span.category == category)
+            .collect::<Vec<_>>();
+
+        let shares = models
+            .iter()
+            .map(|model| {
+                (
+                    model.year,
+                    time_streamed(model, category),
+                    share(model, category),
+                )
+            })
+            .collect::<Vec<_>>();
+        let max_share = shares
+            .iter()
+            .map(|(.., share)| *share)
+            .fold(f64::EPSILON, f64::max);
+
+        Some(Self {
+            category: category.to_owned(),
+            category_image,
+            year,
+            total_min,
+            share: ratio(total_min, all_min),
+            days: streams
+                .iter()
+                .flat_map(|stream| stream.day_iter().map(|(day, _)| day))
+                .collect::<HashSet<_>>()
+                .len(),
+            streams: streams.len(),
+            first_streamed: spans.iter().map(|span| span.first_streamed).min(),
+            last_streamed: spans.iter().map(|span| span.last_streamed).max(),
+            years: shares
+                .into_iter()
+                .map(|(model_year, total_min, share)| GameYear {
+                    year: model_year,
+                    total_min,
+                    share,
+                    relative: share / max_share,
+                    selected: year == Some(model_year),
+                })
+                .collect(),
+        })
+    }
+}
+
+fn time_streamed(model: &StreamerModel, category: &str) -> u64 {
+    model
+        .games
+        .iter()
+        .find(|game| game.category == category)
+        .map(|game| game.time_streamed_min)
+        .unwrap_or_default()
+}
+
+fn share(model: &StreamerModel, category: &str) -> f64 {
+    ratio(time_streamed(model, category), model.total_time_min)
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
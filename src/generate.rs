@@ -0,0 +1,68 @@
+use crate::{
+    assets::Assets,
+    data_actor::{DataActor, GetAllTime, GetAllYears, GetData},
+    period::Period,
+    render_period,
+    templates::Templates,
+};
+use actix::Addr;
+use anyhow::{anyhow, Context, Result};
+use std::{fs, path::Path};
+
+/// Writes the pages of all tracked years, their API payloads and the static files to `out`.
+///
+/// The paths match the routes of the server, except that API payloads end in `.json`
+/// and pages are written as `index.html` (e.g. `prev/2023/index.html`).
+pub async fn generate(
+    out: &Path,
+    actor: &Addr<DataActor>,
+    templates: &Templates,
+    static_files: &Assets,
+) -> Result<()> {
+    let (models, _) = actor.send(GetAllYears).await??;
+    let (current, prev) = models
+        .split_last()
+        .ok_or_else(|| anyhow!("No year is tracked"))?;
+    let data = actor.clone().recipient::<GetData>();
+
+    let pages = prev
+        .iter()
+        .map(|model| {
+            (
+                Period::Year(model.year),
+                format!("prev/{}/index.html", model.year),
+                format!("api/v1/prev/{}.json", model.year),
+                model,
+            )
+        })
+        .chain([(
+            Period::CurrentYear,
+            "index.html".to_owned(),
+            "api/v1/current.json".to_owned(),
+            current,
+        )]);
+    for (period, page, api, model) in pages {
+        let html = render_period(&data, templates, period)
+            .await
+            .map_err(|e| anyhow!("Failed to render {page}: {e}"))?;
+        write(&out.join(page), html)?;
+        write(&out.join(api), serde_json::to_vec(&**model)?)?;
+    }
+    let (all_time, _) = actor.send(GetAllTime).await??;
+    write(
+        &out.join("api/v1/all-time.json"),
+        serde_json::to_vec(&*all_time)?,
+    )?;
+
+    static_files.copy_to(out)?;
+    eprintln!("Generated {} year(s) in {}", models.len(), out.display());
+    Ok(())
+}
+
+fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
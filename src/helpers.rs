@@ -1,6 +1,7 @@
 use handlebars::{
     Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderErrorReason,
 };
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use std::time::Duration;
 
 pub fn bar_width(
@@ -73,3 +74,52 @@ pub fn format_hours(
     }
     Ok(())
 }
+
+pub fn fixed(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = h
+        .param(0)
+        .and_then(|p| p.value().as_f64())
+        .ok_or_else(|| RenderErrorReason::InvalidParamType("[0]: expected f64"))?;
+    let digits = h
+        .param(1)
+        .and_then(|p| p.value().as_u64())
+        .ok_or_else(|| RenderErrorReason::InvalidParamType("[1]: expected u64 (digits)"))?;
+    out.write(&format!("{value:.*}", digits as usize))?;
+    Ok(())
+}
+
+pub fn signed(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = h
+        .param(0)
+        .and_then(|p| p.value().as_f64())
+        .ok_or_else(|| RenderErrorReason::InvalidParamType("[0]: expected f64"))?;
+    out.write(&format!("{:+}", value.round() as i64))?;
+    Ok(())
+}
+
+pub fn url_component(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = h
+        .param(0)
+        .and_then(|p| p.value().as_str())
+        .ok_or_else(|| RenderErrorReason::InvalidParamType("[0]: expected string"))?;
+    out.write(&utf8_percent_encode(value, NON_ALPHANUMERIC).to_string())?;
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use crate::{model::StreamerModel, period::Period, streamcounter::Ditch};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+/// Used for the UIDs of events.
+const DOMAIN: &str = "arewevarietyyet.nerixyz.de";
+/// Lines longer than this (in bytes) are folded.
+const MAX_LINE: usize = 75;
+
+/// An iCalendar feed with every stream as an event
+/// and every ditch of at least `min_ditch_days` full days as an all-day event.
+pub fn calendar(period: Period, model: &StreamerModel, min_ditch_days: i64) -> String {
+    let mut cal = Calendar::default();
+    cal.line("BEGIN:VCALENDAR");
+    cal.line("VERSION:2.0");
+    cal.line(&format!("PRODID:-//{DOMAIN}//Streams//EN"));
+    cal.line(&format!(
+        "X-WR-CALNAME:{}",
+        escape(&format!("Streams {}", model.period_label))
+    ));
+    let now = format_time(Utc::now());
+
+    // oldest first
+    for stream in model.streams.iter().rev() {
+        let games = stream
+            .games
+            .iter()
+            .map(|game| game.category.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        cal.line("BEGIN:VEVENT");
+        cal.line(&format!(
+            "UID:stream-{}@{DOMAIN}",
+            stream.start_date_time.timestamp()
+        ));
+        cal.line(&format!("DTSTAMP:{now}"));
+        cal.line(&format!("DTSTART:{}", format_time(stream.start_date_time)));
+        cal.line(&format!("DTEND:{}", format_time(stream.end_date_time())));
+        cal.line(&format!(
+            "SUMMARY:{}",
+            escape(&match games.as_str() {
+                "" => "Stream".to_owned(),
+                games => format!("Stream: {games}"),
+            })
+        ));
+        cal.line("END:VEVENT");
+    }
+
+    let streams = model.enclosed_streams();
+    let mut ditches = Ditch::top(period, &streams, streams.len() + 1)
+        .into_iter()
+        .filter_map(|ditch| {
+            // only full days are ditched
+            let from = ditch.from.date_naive() + Duration::days(1);
+            let to = ditch.to.date_naive();
+            ((to - from).num_days() >= min_ditch_days.max(1)).then_some((from, to))
+        })
+        .collect::<Vec<_>>();
+    ditches.sort_unstable();
+    for (from, to) in ditches {
+        let days = (to - from).num_days();
+        cal.line("BEGIN:VEVENT");
+        cal.line(&format!("UID:ditch-{}@{DOMAIN}", format_date(from)));
+        cal.line(&format!("DTSTAMP:{now}"));
+        cal.line(&format!("DTSTART;VALUE=DATE:{}", format_date(from)));
+        // exclusive
+        cal.line(&format!("DTEND;VALUE=DATE:{}", format_date(to)));
+        cal.line(&format!("SUMMARY:Ditched ({days} days)"));
+        cal.line("TRANSP:TRANSPARENT");
+        cal.line("END:VEVENT");
+    }
+
+    cal.line("END:VCALENDAR");
+    cal.0
+}
+
+#[derive(Default)]
+struct Calendar(String);
+
+impl Calendar {
+    /// Writes a content line, folded after [`MAX_LINE`] bytes.
+    fn line(&mut self, line: &str) {
+        let mut width = 0;
+        for c in line.chars() {
+            if width + c.len_utf8() > MAX_LINE {
+                self.0.push_str("\r\n ");
+                width = 1;
+            }
+            self.0.push(c);
+            width += c.len_utf8();
+        }
+        self.0.push_str("\r\n");
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ical::{calendar, escape, Calendar},
+        model::{ModelOptions, StreamerModel, Surrounding},
+        period::Period,
+        sullygnome::StreamData,
+    };
+
+    #[test]
+    fn folds_and_escapes() {
+        assert_eq!(escape("Stream: a, b; c"), "Stream: a\\, b\\; c");
+        let mut cal = Calendar::default();
+        cal.line(&format!("SUMMARY:{}", "a".repeat(80)));
+        let lines = cal.0.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1], format!(" {}", "a".repeat(13)));
+    }
+
+    #[test]
+    fn includes_ditches_across_the_start() {
+        let stream = |start: &str| StreamData {
+            start_date_time: start.parse().unwrap(),
+            length: 60,
+            games: Vec::new(),
+            clamped: false,
+        };
+        let model = StreamerModel::create(
+            Period::Year(2022),
+            &ModelOptions::default(),
+            Vec::new(),
+            vec![
+                stream("2022-12-31T18:00:00Z"),
+                stream("2022-01-10T18:00:00Z"),
+            ],
+            Surrounding {
+                before: Some(stream("2021-12-20T18:00:00Z")),
+                after: None,
+            },
+        )
+        .unwrap();
+        let cal = calendar(Period::Year(2022), &model, 3);
+        assert!(cal.contains("DTSTART;VALUE=DATE:20211221\r\nDTEND;VALUE=DATE:20220110"));
+    }
+}
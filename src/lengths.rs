@@ -0,0 +1,168 @@
+use crate::sullygnome::StreamData;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Streams are grouped by hours, everything longer than this ends up in the last bucket.
+const HISTOGRAM_HOURS: i64 = 8;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamLengths {
+    pub count: usize,
+    pub average_min: u64,
+    pub median_min: u64,
+    pub longest: Option<LongestStream>,
+    pub histogram: Vec<LengthBucket>,
+    pub short_threshold_min: i64,
+    /// Streams shorter than `short_threshold_min`.
+    pub short_streams: usize,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LongestStream {
+    pub start: DateTime<Utc>,
+    pub length_min: u64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LengthBucket {
+    pub label: String,
+    pub count: usize,
+    /// Relative to the largest bucket.
+    pub relative: f64,
+}
+
+impl StreamLengths {
+    pub fn calculate(streams: &[StreamData], short_threshold_min: i64) -> Self {
+        let mut lengths = streams
+            .iter()
+            .map(|stream| stream.length.max(0))
+            .collect::<Vec<_>>();
+        lengths.sort_unstable();
+
+        let mut buckets = [0usize; HISTOGRAM_HOURS as usize + 1];
+        for length in &lengths {
+            buckets[(length / 60).min(HISTOGRAM_HOURS) as usize] += 1;
+        }
+        let max_bucket = buckets.iter().copied().max().unwrap_or_default().max(1);
+
+        Self {
+            count: lengths.len(),
+            average_min: lengths
+                .iter()
+                .sum::<i64>()
+                .checked_div(lengths.len() as i64)
+                .unwrap_or_default() as u64,
+            median_min: lengths.get(lengths.len() / 2).copied().unwrap_or_default() as u64,
+            longest: streams
+                .iter()
+                .max_by_key(|stream| stream.length)
+                .map(|stream| LongestStream {
+                    start: stream.start_date_time,
+                    length_min: stream.length.max(0) as u64,
+                }),
+            histogram: buckets
+                .iter()
+                .enumerate()
+                .map(|(hours, count)| LengthBucket {
+                    label: match hours as i64 {
+                        0 => "<1h".to_owned(),
+                        HISTOGRAM_HOURS => format!("{HISTOGRAM_HOURS}h+"),
+                        hours => format!("{hours}-{}h", hours + 1),
+                    },
+                    count: *count,
+                    relative: *count as f64 / max_bucket as f64,
+                })
+                .collect(),
+            short_threshold_min,
+            short_streams: lengths
+                .iter()
+                .filter(|length| **length < short_threshold_min)
+                .count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lengths::StreamLengths,
+        model::{ModelOptions, StreamerModel, Surrounding},
+        period::Period,
+        sullygnome::StreamData,
+    };
+
+    fn stream(day: u32, length: i64) -> StreamData {
+        StreamData {
+            start_date_time: format!("2022-01-{day:02}T18:00:00Z").parse().unwrap(),
+            length,
+            games: Vec::new(),
+            clamped: false,
+        }
+    }
+
+    #[test]
+    fn buckets_lengths_by_hour() {
+        let lengths = StreamLengths::calculate(
+            &[
+                stream(1, -5),
+                stream(2, 0),
+                stream(3, 59),
+                stream(4, 60),
+                stream(5, 119),
+                stream(6, 479),
+                stream(7, 480),
+                stream(8, 1000),
+            ],
+            60,
+        );
+        let counts = lengths
+            .histogram
+            .iter()
+            .map(|bucket| (bucket.label.as_str(), bucket.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            [
+                ("<1h", 3),
+                ("1-2h", 2),
+                ("2-3h", 0),
+                ("3-4h", 0),
+                ("4-5h", 0),
+                ("5-6h", 0),
+                ("6-7h", 0),
+                ("7-8h", 1),
+                ("8h+", 2),
+            ]
+        );
+        assert_eq!(lengths.histogram[0].relative, 1.0);
+        // a stream of exactly the threshold isn't short
+        assert_eq!(lengths.short_streams, 3);
+        assert_eq!(lengths.median_min, 119);
+        assert_eq!(lengths.longest.unwrap().length_min, 1000);
+    }
+
+    #[test]
+    fn uses_short_stream_min() {
+        let streams = vec![stream(1, 30), stream(2, 90), stream(3, 150)];
+        let model = |short_stream_min| {
+            StreamerModel::create(
+                Period::Year(2022),
+                &ModelOptions {
+                    short_stream_min,
+                    ..Default::default()
+                },
+                Vec::new(),
+                streams.clone(),
+                Surrounding::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(model(60).lengths.short_streams, 1);
+        assert_eq!(model(120).lengths.short_streams, 2);
+        assert_eq!(model(120).lengths.short_threshold_min, 120);
+        assert_eq!(StreamLengths::calculate(&[], 60).histogram[0].relative, 0.0);
+    }
+}
@@ -1,83 +1,508 @@
 use crate::{
-    data_actor::{DataActor, GetData},
-    model::Year,
+    caching::Validators,
+    config::{Cli, Command, Config},
+    data_actor::{DataActor, GetAllTime, GetAllYears, GetData, GetYears, ROLLING_WINDOWS},
+    errors::{data_error, mailbox_error, render_error_page},
+    export::Export,
+    game::GamePage,
+    period::Period,
+    templates::{Site, Templates},
 };
 use actix::{Actor, Recipient};
-use actix_files::Files;
-use actix_web::{error, get, http::header::ContentType, web, App, HttpResponse, HttpServer};
-use handlebars::{DirectorySourceOptions, Handlebars};
+use actix_web::{
+    error, get,
+    http::header::{self, ContentType},
+    middleware::ErrorHandlers,
+    web, App, HttpRequest, HttpResponse, HttpServer,
+};
+use anyhow::Context;
+use chrono::{Datelike, NaiveDate, Utc};
+use clap::Parser;
+use futures::future;
 use model::StreamerModel;
-use serde::Serialize;
-use std::io;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
+mod adherence;
+mod assets;
+mod breaks;
+mod caching;
+mod categories;
+mod compare;
+mod config;
 mod data_actor;
 mod datetime;
+mod diversity;
+mod errors;
+mod export;
+mod game;
+mod generate;
 mod helpers;
+mod ical;
+mod lengths;
 mod model;
+mod period;
+mod projection;
+mod status;
 mod streamcounter;
 mod sullygnome;
+mod templates;
+mod weekly;
 
 #[derive(Serialize)]
-struct TemplateContext<'a> {
+struct TemplateContext<'a, P = ()> {
+    site: &'a Site,
     streamer: &'a StreamerModel,
     years: &'a Vec<i32>,
+    rolling: Vec<RollingContext<'a>>,
     child: &'static str,
+    /// Data specific to the `child` template.
+    page: P,
 }
 
-async fn render_template(
-    actor: web::Data<Recipient<GetData>>,
-    handlebars: web::Data<Handlebars<'_>>,
-    year: Year,
+#[derive(Serialize)]
+struct RollingContext<'a> {
+    days: u32,
+    streamer: &'a StreamerModel,
+}
+
+/// Windows that can't be created (e.g. because last year isn't tracked) are skipped.
+async fn rolling_models(actor: &Recipient<GetData>) -> Vec<(u32, Arc<StreamerModel>)> {
+    future::join_all(ROLLING_WINDOWS.map(|days| async move {
+        let (model, _) = actor
+            .send(GetData(Period::LastDays(days)))
+            .await
+            .ok()?
+            .ok()?;
+        Some((days, model))
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn render_html<P: Serialize>(
+    templates: &Templates,
+    ctx: &TemplateContext<'_, P>,
+) -> Result<String, actix_web::Error> {
+    templates
+        .render("skeleton", ctx)
+        .map_err(error::ErrorInternalServerError)
+}
+
+fn render<P: Serialize>(
+    templates: &Templates,
+    ctx: &TemplateContext<'_, P>,
+    validators: &Validators,
 ) -> Result<HttpResponse, actix_web::Error> {
+    let rendered = render_html(templates, ctx)?;
+    Ok(validators
+        .headers(&mut HttpResponse::Ok())
+        .insert_header(ContentType::html())
+        .body(rendered))
+}
+
+/// Renders the page of a `period` (excluding comparisons and all-time).
+async fn render_period(
+    actor: &Recipient<GetData>,
+    templates: &Templates,
+    period: Period,
+) -> Result<String, actix_web::Error> {
     let (streamer, years) = actor
-        .send(GetData(year))
+        .send(GetData(period))
         .await
-        .map_err(error::ErrorTooManyRequests)?
-        .map_err(error::ErrorInternalServerError)?;
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    period_html(actor, templates, period, &streamer, &years).await
+}
+
+async fn period_html(
+    actor: &Recipient<GetData>,
+    templates: &Templates,
+    period: Period,
+    streamer: &StreamerModel,
+    years: &Vec<i32>,
+) -> Result<String, actix_web::Error> {
+    let rolling = match period {
+        Period::CurrentYear => rolling_models(actor).await,
+        _ => Vec::new(),
+    };
     let ctx = TemplateContext {
-        streamer: &streamer,
-        years: &years,
-        child: match year {
-            Year::Current => "this-year",
-            Year::Last(_) => "last-year",
+        site: templates.site(),
+        streamer,
+        years,
+        rolling: rolling
+            .iter()
+            .map(|(days, streamer)| RollingContext {
+                days: *days,
+                streamer,
+            })
+            .collect(),
+        child: match period {
+            Period::CurrentYear => "this-year",
+            Period::Year(_) => "last-year",
+            _ => "period",
         },
+        page: (),
     };
-    let rendered = handlebars
-        .render("skeleton", &ctx)
-        .map_err(error::ErrorInternalServerError)?;
-    Ok(HttpResponse::Ok()
+    render_html(templates, &ctx)
+}
+
+async fn render_template(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+    templates: web::Data<Templates>,
+    period: Period,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (streamer, years) = actor
+        .send(GetData(period))
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let validators = Validators::new(
+        streamer.fetched_at,
+        templates.generation(),
+        caching::is_final(period),
+    );
+    if let Some(res) = validators.not_modified(&req) {
+        return Ok(res);
+    }
+    let rendered = period_html(&actor, &templates, period, &streamer, &years).await?;
+    Ok(validators
+        .headers(&mut HttpResponse::Ok())
         .insert_header(ContentType::html())
         .body(rendered))
 }
 
+#[get("/")]
 async fn index(
+    req: HttpRequest,
     actor: web::Data<Recipient<GetData>>,
-    handlebars: web::Data<Handlebars<'_>>,
+    templates: web::Data<Templates>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    render_template(actor, handlebars, Year::Current).await
+    render_template(req, actor, templates, Period::CurrentYear).await
+}
+
+/// Everything that's not a route or a static file.
+async fn not_found() -> Result<HttpResponse, actix_web::Error> {
+    Err(error::ErrorNotFound("This page doesn't exist"))
 }
 
 #[get("/prev/{year}")]
 async fn last_year(
+    req: HttpRequest,
     actor: web::Data<Recipient<GetData>>,
-    handlebars: web::Data<Handlebars<'_>>,
+    templates: web::Data<Templates>,
     path: web::Path<i32>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    render_template(actor, handlebars, Year::Last(path.into_inner())).await
+    render_template(req, actor, templates, Period::Year(path.into_inner())).await
+}
+
+#[get("/month/{year}/{month}")]
+async fn month(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+    templates: web::Data<Templates>,
+    path: web::Path<(i32, u32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (year, month) = path.into_inner();
+    let period = Period::month(year, month).map_err(error::ErrorBadRequest)?;
+    render_template(req, actor, templates, period).await
+}
+
+#[get("/week/{year}/{week}")]
+async fn week(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+    templates: web::Data<Templates>,
+    path: web::Path<(i32, u32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (year, week) = path.into_inner();
+    let period = Period::week(year, week).map_err(error::ErrorBadRequest)?;
+    render_template(req, actor, templates, period).await
+}
+
+#[get("/last/{days}")]
+async fn last_days(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+    templates: web::Data<Templates>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let period = Period::last_days(&path).map_err(error::ErrorBadRequest)?;
+    render_template(req, actor, templates, period).await
+}
+
+async fn render_comparison(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllYears>>,
+    templates: web::Data<Templates>,
+    selected: Option<(i32, i32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (models, years) = actor
+        .send(GetAllYears)
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let current = models
+        .last()
+        .ok_or_else(|| error::ErrorInternalServerError("No year is tracked"))?;
+    let validators = caching::all_years_validators(&models, templates.generation(), false);
+    if let Some(res) = validators.not_modified(&req) {
+        return Ok(res);
+    }
+    let compared = match selected {
+        Some(selected) => {
+            let find = |year: i32| {
+                models
+                    .iter()
+                    .find(|model| model.year == year)
+                    .map(|model| &**model)
+                    .ok_or_else(|| error::ErrorNotFound(format!("{year} isn't tracked")))
+            };
+            vec![find(selected.0)?, find(selected.1)?]
+        }
+        None => models.iter().map(|model| &**model).collect(),
+    };
+    let ctx = TemplateContext {
+        site: templates.site(),
+        streamer: current,
+        years: &years,
+        rolling: Vec::new(),
+        child: "compare",
+        page: compare::compare(&compared),
+    };
+    render(&templates, &ctx, &validators)
+}
+
+#[get("/compare")]
+async fn compare_all(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllYears>>,
+    templates: web::Data<Templates>,
+) -> Result<HttpResponse, actix_web::Error> {
+    render_comparison(req, actor, templates, None).await
+}
+
+#[get("/compare/{a}/{b}")]
+async fn compare_years(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllYears>>,
+    templates: web::Data<Templates>,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    render_comparison(req, actor, templates, Some(path.into_inner())).await
+}
+
+async fn render_game(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllYears>>,
+    templates: web::Data<Templates>,
+    category: &str,
+    year: Option<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (models, years) = actor
+        .send(GetAllYears)
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let current = models
+        .last()
+        .ok_or_else(|| error::ErrorInternalServerError("No year is tracked"))?;
+    if let Some(year) = year.filter(|year| !models.iter().any(|model| model.year == *year)) {
+        return Err(error::ErrorNotFound(format!("{year} isn't tracked")));
+    }
+    // the current year is shown next to past years, but a past year itself doesn't change
+    let validators = caching::all_years_validators(
+        &models,
+        templates.generation(),
+        year.is_some_and(|year| caching::is_final(Period::Year(year))),
+    );
+    if let Some(res) = validators.not_modified(&req) {
+        return Ok(res);
+    }
+    let models = models.iter().map(|model| &**model).collect::<Vec<_>>();
+    let page = GamePage::create(category, year, &models)
+        .ok_or_else(|| error::ErrorNotFound(format!("{category} wasn't streamed")))?;
+    let ctx = TemplateContext {
+        site: templates.site(),
+        streamer: current,
+        years: &years,
+        rolling: Vec::new(),
+        child: "game",
+        page,
+    };
+    render(&templates, &ctx, &validators)
+}
+
+#[get("/game/{category}")]
+async fn game_all_years(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllYears>>,
+    templates: web::Data<Templates>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, actix_web::Error> {
+    render_game(req, actor, templates, &path, None).await
+}
+
+#[get("/game/{category}/{year}")]
+async fn game_in_year(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllYears>>,
+    templates: web::Data<Templates>,
+    path: web::Path<(String, i32)>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (category, year) = path.into_inner();
+    render_game(req, actor, templates, &category, Some(year)).await
+}
+
+#[get("/all-time")]
+async fn all_time(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllTime>>,
+    templates: web::Data<Templates>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (streamer, years) = actor
+        .send(GetAllTime)
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let validators = Validators::new(streamer.fetched_at, templates.generation(), false);
+    if let Some(res) = validators.not_modified(&req) {
+        return Ok(res);
+    }
+    let ctx = TemplateContext {
+        site: templates.site(),
+        streamer: &streamer,
+        years: &years,
+        rolling: Vec::new(),
+        child: "period",
+        page: (),
+    };
+    render(&templates, &ctx, &validators)
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    year: Option<i32>,
+}
+
+/// Raw data of a year (the current one by default) as CSV or JSON lines.
+#[get("/export/{file}")]
+async fn export_data(
+    actor: web::Data<Recipient<GetData>>,
+    path: web::Path<String>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let export = path.parse::<Export>().map_err(error::ErrorNotFound)?;
+    let period = match query.year {
+        Some(year) if year != Utc::now().year() => Period::Year(year),
+        _ => Period::CurrentYear,
+    };
+    let (model, _) = actor
+        .send(GetData(period))
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let body = export
+        .write(&model)
+        .map_err(error::ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok()
+        .content_type(export.format.content_type())
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}-{path}\"", model.year),
+        ))
+        .body(body))
+}
+
+#[derive(Deserialize)]
+struct CalendarQuery {
+    /// Ditches shorter than this aren't included.
+    min_ditch_days: Option<i64>,
+}
+
+/// Ditches need to be at least this long (in days) to be in the calendar by default.
+const CALENDAR_MIN_DITCH_DAYS: i64 = 3;
+/// Number of days in the rolling calendar.
+const CALENDAR_ROLLING_DAYS: u32 = 90;
+
+async fn calendar(
+    actor: web::Data<Recipient<GetData>>,
+    period: Period,
+    query: web::Query<CalendarQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (model, _) = actor
+        .send(GetData(period))
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(ical::calendar(
+            period,
+            &model,
+            query.min_ditch_days.unwrap_or(CALENDAR_MIN_DITCH_DAYS),
+        )))
+}
+
+#[get("/calendar.ics")]
+async fn calendar_rolling(
+    actor: web::Data<Recipient<GetData>>,
+    query: web::Query<CalendarQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    calendar(actor, Period::LastDays(CALENDAR_ROLLING_DAYS), query).await
+}
+
+#[get("/calendar/{year}.ics")]
+async fn calendar_year(
+    actor: web::Data<Recipient<GetData>>,
+    path: web::Path<i32>,
+    query: web::Query<CalendarQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let year = path.into_inner();
+    let period = if year == Utc::now().year() {
+        Period::CurrentYear
+    } else {
+        Period::Year(year)
+    };
+    calendar(actor, period, query).await
+}
+
+#[derive(Deserialize)]
+struct RangeQuery {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+#[get("/range")]
+async fn range(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+    templates: web::Data<Templates>,
+    query: web::Query<RangeQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let period = Period::range(query.from, query.to).map_err(error::ErrorBadRequest)?;
+    render_template(req, actor, templates, period).await
 }
 
 #[get("/custom-api")]
 async fn custom_api(
+    req: HttpRequest,
     actor: web::Data<Recipient<GetData>>,
 ) -> Result<HttpResponse, actix_web::Error> {
     let (model, _) = actor
-        .send(GetData(Year::Current))
+        .send(GetData(Period::CurrentYear))
         .await
-        .map_err(|_| {
-            actix_web::error::ErrorTooManyRequests("🤯 Actor mailbox closed or we timed out.")
-        })?
-        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("🚨 API failed: {e}")))?;
-    Ok(HttpResponse::Ok()
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let validators = Validators::new(model.fetched_at, 0, false);
+    if let Some(res) = validators.not_modified(&req) {
+        return Ok(res);
+    }
+    Ok(validators
+        .headers(&mut HttpResponse::Ok())
         .insert_header(ContentType::plaintext())
         .body(format!(
             "{prefix} {p_variety}% variety this year. {n_days_ditched}/{n_days} days ({p_days_ditched}%) ditched.",
@@ -92,37 +517,129 @@ async fn custom_api(
         )))
 }
 
+async fn api_model(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+    period: Period,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (model, _) = actor
+        .send(GetData(period))
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let validators = Validators::new(model.fetched_at, 0, caching::is_final(period));
+    Ok(match validators.not_modified(&req) {
+        Some(res) => res,
+        None => validators.headers(&mut HttpResponse::Ok()).json(&*model),
+    })
+}
+
+#[get("/v1/current")]
+async fn api_current(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    api_model(req, actor, Period::CurrentYear).await
+}
+
+#[get("/v1/prev/{year}")]
+async fn api_last_year(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetData>>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, actix_web::Error> {
+    api_model(req, actor, Period::Year(path.into_inner())).await
+}
+
+#[get("/v1/all-time")]
+async fn api_all_time(
+    req: HttpRequest,
+    actor: web::Data<Recipient<GetAllTime>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (model, _) = actor
+        .send(GetAllTime)
+        .await
+        .map_err(mailbox_error)?
+        .map_err(data_error)?;
+    let validators = Validators::new(model.fetched_at, 0, false);
+    Ok(match validators.not_modified(&req) {
+        Some(res) => res,
+        None => validators.headers(&mut HttpResponse::Ok()).json(&*model),
+    })
+}
+
 #[actix_web::main]
-async fn main() -> io::Result<()> {
-    let actor = DataActor::start_default();
+async fn main() -> anyhow::Result<()> {
+    let mut cli = Cli::parse();
+    let command = cli.command.take().unwrap_or(Command::Serve);
+    let config = Config::load(cli)?;
+    match command {
+        Command::Serve => {
+            config.validate_assets()?;
+            serve(config).await
+        }
+        Command::Generate { out } => {
+            config.validate_assets()?;
+            let actor = DataActor::new(config.model_options()?, config.fetch_options())
+                .wait_for_load()
+                .start();
+            let site = Site {
+                static_site: true,
+                ..config.site()
+            };
+            let templates = Templates::load(config.templates(), site, config.dev)?;
+            generate::generate(&out, &actor, &templates, &config.static_files()).await
+        }
+        Command::Status { year, json } => {
+            status::status(&config.model_options()?, config.fetch_options(), year, json).await
+        }
+    }
+}
+
+async fn serve(config: Config) -> anyhow::Result<()> {
+    let actor = DataActor::new(config.model_options()?, config.fetch_options()).start();
+    let templates = Templates::load(config.templates(), config.site(), config.dev)?;
+    let all_years_actor = web::Data::new(actor.clone().recipient::<GetAllYears>());
+    let all_time_actor = web::Data::new(actor.clone().recipient::<GetAllTime>());
+    let years_actor = web::Data::new(actor.clone().recipient::<GetYears>());
     let actor = web::Data::new(actor.recipient::<GetData>());
-    let mut handlebars = Handlebars::new();
-    handlebars
-        .register_templates_directory("templates", {
-            let mut opts = DirectorySourceOptions::default();
-            opts.tpl_extension = ".hbs.html".to_owned();
-            opts
-        })
-        .unwrap();
-    handlebars.register_helper("bar-width", Box::new(helpers::bar_width));
-    handlebars.register_helper("humanize-min", Box::new(helpers::humanize_min));
-    handlebars.register_helper("round-percent", Box::new(helpers::rounded_percent));
-    handlebars.register_helper("format-hours", Box::new(helpers::format_hours));
-    let handlebars = web::Data::new(handlebars);
+    let templates = web::Data::new(templates);
 
+    let static_files = config.static_files();
     HttpServer::new(move || {
         App::new()
             .app_data(actor.clone())
-            .app_data(handlebars.clone())
-            .service(web::scope("/api").service(custom_api))
-            .service(last_year)
+            .app_data(all_years_actor.clone())
+            .app_data(all_time_actor.clone())
+            .app_data(years_actor.clone())
+            .app_data(templates.clone())
+            .wrap(ErrorHandlers::new().default_handler(render_error_page))
             .service(
-                Files::new("/", "static")
-                    .index_file("this_file_doesnt_exist_but_we_dont_need_it")
-                    .default_handler(web::route().to(index)),
+                web::scope("/api")
+                    .service(custom_api)
+                    .service(api_current)
+                    .service(api_last_year)
+                    .service(api_all_time),
             )
+            .service(index)
+            .service(last_year)
+            .service(month)
+            .service(week)
+            .service(last_days)
+            .service(range)
+            .service(compare_all)
+            .service(compare_years)
+            .service(all_time)
+            .service(game_all_years)
+            .service(game_in_year)
+            .service(export_data)
+            .service(calendar_rolling)
+            .service(calendar_year)
+            .configure(|cfg| static_files.configure_static(cfg, web::route().to(not_found)))
     })
-    .bind("127.0.0.1:8934")?
+    .bind(config.bind)
+    .with_context(|| format!("Failed to bind to {}", config.bind))?
     .run()
-    .await
+    .await?;
+    Ok(())
 }
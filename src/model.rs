@@ -1,38 +1,64 @@
 use crate::{
-    datetime::{days_in_current_year, days_in_year, first_day_in_year},
-    streamcounter::{self, LongestDitch},
-    sullygnome,
+    adherence::{Adherence, DeclaredSchedule},
+    breaks::{Break, Breaks},
+    categories::CategoryTimeline,
+    diversity::{Diversity, Metric},
+    lengths::StreamLengths,
+    period::Period,
+    projection::Projection,
+    streamcounter::{self, Ditch, LongestDitch, Streaks},
+    sullygnome::{self, StreamData},
+    weekly::{WeeklyHeatmap, DEFAULT_TIMEZONE},
 };
 use anyhow::{anyhow, Result};
-use chrono::{Datelike, Utc};
+use chrono::{DateTime, Datelike, Utc};
+use chrono_tz::Tz;
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-lazy_static! {
-    static ref GAME_REGEX: Regex = Regex::new("^([^|]+)\\|(?:[^|]+)\\|(.+)$").unwrap();
-}
+/// The minimum share of variety to count as variety, if not configured otherwise.
+pub const DEFAULT_VARIETY_THRESHOLD: f64 = 0.3;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum Year {
-    Current,
-    Last(i32),
+/// Settings for creating models that can be changed at startup.
+#[derive(Debug, Clone)]
+pub struct ModelOptions {
+    pub headline: Metric,
+    /// Streams shorter than this (in minutes) are counted as short streams.
+    pub short_stream_min: i64,
+    /// The minimum share of variety to count as variety.
+    pub variety_threshold: f64,
+    /// Announced breaks, which don't count as ditched.
+    pub breaks: Arc<Breaks>,
+    /// The announced weekly schedule.
+    pub schedule: Arc<DeclaredSchedule>,
+    /// The timezone of the schedule and the weekly heatmap.
+    pub timezone: Tz,
 }
 
-impl Year {
-    pub fn days_till_today(self) -> usize {
-        match self {
-            Year::Current => days_in_current_year(),
-            Year::Last(year) => days_in_year(year),
+impl Default for ModelOptions {
+    fn default() -> Self {
+        Self {
+            headline: Metric::default(),
+            short_stream_min: 60,
+            variety_threshold: DEFAULT_VARIETY_THRESHOLD,
+            breaks: Default::default(),
+            schedule: Default::default(),
+            timezone: DEFAULT_TIMEZONE,
         }
     }
+}
 
-    pub fn number(self) -> i32 {
-        match self {
-            Year::Current => Utc::now().year(),
-            Year::Last(n) => n,
-        }
-    }
+/// Number of ditches in [`StreamerModel::top_ditches`].
+const TOP_DITCHES: usize = 10;
+
+lazy_static! {
+    static ref GAME_REGEX: Regex = Regex::new("^([^|]+)\\|(?:[^|]+)\\|(.+)$").unwrap();
 }
 
 type Streamtime = Vec<f32>;
@@ -46,72 +72,233 @@ pub struct StreamerModel {
     pub variety_percent: f64,
     pub ow_percent: f64,
     pub are_we_variety: bool,
+    pub diversity: Diversity,
+    pub variety_description: &'static str,
+    pub timeline: CategoryTimeline,
 
     pub days_ditched: usize,
     pub days_until_now: usize,
     pub percent_ditched: f64,
+    /// Days without a stream during an announced break.
+    pub days_excused: usize,
+    pub adjusted_days_ditched: usize,
+    pub adjusted_percent_ditched: f64,
+    /// Announced breaks overlapping this period.
+    pub breaks: Vec<Break>,
 
     pub days: Streamtime,
+    /// Whether a day without a stream is excused by a break.
+    pub excused_days: Vec<bool>,
+    pub day_games: Vec<String>,
     pub max_streamtime: f32,
-    pub start_of_year_offset: u32,
+    pub start_offset: u32,
 
     pub year: i32,
+    pub period_label: String,
+    pub period_start: DateTime<Utc>,
+    pub ongoing: bool,
 
     pub longest_ditch: LongestDitch,
+    pub top_ditches: Vec<Ditch>,
+    pub streaks: Streaks,
+    pub adherence: Option<Adherence>,
+
+    pub projection: Option<Projection>,
+    pub weekly: WeeklyHeatmap,
+    pub lengths: StreamLengths,
+
+    /// Streams in this period, sorted from newest to oldest.
+    #[serde(skip)]
+    pub streams: Vec<StreamData>,
+    /// The streams right before and after the period.
+    #[serde(skip)]
+    pub surrounding: Surrounding,
+    /// When the data of this model was fetched.
+    #[serde(skip)]
+    pub fetched_at: DateTime<Utc>,
 }
 
-fn fill_days(year: Year, streams: &sullygnome::StreamsResponse) -> (Streamtime, f32) {
+fn fill_days(period: Period, streams: &[StreamData]) -> (Streamtime, Vec<String>, f32) {
     let mut max = 0.1f32;
-    let mut streamtime = vec![0.0f32; days_in_year(year.number())];
-    for stream in &streams.data {
+    let n_days = period.days();
+    let start = period.bounds().0.date_naive();
+    let mut streamtime = vec![0.0f32; n_days];
+    let mut games = vec![Vec::<&str>::new(); n_days];
+    // iterate from oldest to newest, so the games of a day are in order
+    for stream in streams.iter().rev() {
         for (day, time) in stream.day_iter() {
-            streamtime[day as usize] += time;
-            if streamtime[day as usize] > max {
-                max = streamtime[day as usize];
+            let Some(day) = usize::try_from((day - start).num_days())
+                .ok()
+                .filter(|day| *day < n_days)
+            else {
+                continue;
+            };
+            streamtime[day] += time;
+            if streamtime[day] > max {
+                max = streamtime[day];
+            }
+            for game in &stream.games {
+                if !games[day].contains(&game.category.as_str()) {
+                    games[day].push(&game.category);
+                }
+            }
+        }
+    }
+
+    (
+        streamtime,
+        games.into_iter().map(|day| day.join(", ")).collect(),
+        max,
+    )
+}
+
+/// Marks the days of a period that are part of a break and have no stream.
+fn excuse_days(period: Period, breaks: &Breaks, streams: &[StreamData]) -> Vec<bool> {
+    let streamed = streams
+        .iter()
+        .flat_map(|stream| {
+            [
+                stream.start_date_time.date_naive(),
+                stream.end_date_time().date_naive(),
+            ]
+        })
+        .collect::<HashSet<_>>();
+    period
+        .bounds()
+        .0
+        .date_naive()
+        .iter_days()
+        .take(period.days())
+        .map(|day| breaks.contains(day) && !streamed.contains(&day))
+        .collect()
+}
+
+fn ditched_share(ditched: usize, days: usize) -> f64 {
+    let share = ditched as f64 / days as f64;
+    if share.is_nan() {
+        1.0
+    } else {
+        share
+    }
+}
+
+/// The streams right before and after a period.
+/// Ditches are calculated including these, so they aren't cut off at the bounds of a period.
+#[derive(Debug, Default)]
+pub struct Surrounding {
+    pub before: Option<StreamData>,
+    pub after: Option<StreamData>,
+}
+
+impl Surrounding {
+    pub fn find<'a>(period: Period, streams: impl Iterator<Item = &'a StreamData>) -> Self {
+        let (start, end) = period.bounds();
+        let mut surrounding = Self::default();
+        for stream in streams {
+            if stream.end_date_time() <= start
+                && surrounding
+                    .before
+                    .as_ref()
+                    .is_none_or(|before| before.end_date_time() < stream.end_date_time())
+            {
+                surrounding.before = Some(stream.clone());
+            } else if stream.start_date_time >= end
+                && surrounding
+                    .after
+                    .as_ref()
+                    .is_none_or(|after| after.start_date_time > stream.start_date_time)
+            {
+                surrounding.after = Some(stream.clone());
             }
         }
+        surrounding
     }
 
-    (streamtime, max)
+    /// `streams` (sorted from newest to oldest) enclosed by the surrounding streams.
+    fn enclose(&self, streams: &[StreamData]) -> Vec<StreamData> {
+        self.after
+            .iter()
+            .chain(streams)
+            .chain(self.before.iter())
+            .cloned()
+            .collect()
+    }
 }
 
 impl StreamerModel {
-    pub fn create(
-        year: Year,
+    /// Creates a model from the yearly game totals reported by SullyGnome.
+    pub fn from_responses(
+        period: Period,
+        options: &ModelOptions,
         games: sullygnome::GamesResponse,
         streams: sullygnome::StreamsResponse,
+        surrounding: Surrounding,
     ) -> Result<Self> {
         let games = games
             .data
             .into_iter()
             .map(GameModel::try_from)
             .collect::<Result<Vec<_>>>()?;
-        let (total_time_min, longest) = games.iter().fold((0, 0), |(total, longest), item| {
-            (
-                total + item.time_streamed_min,
-                longest.max(item.time_streamed_min),
-            )
-        });
-        let mut ow_percent = if total_time_min != 0 {
-            longest as f64 / total_time_min as f64
-        } else {
-            0.0f64
-        };
-        let mut variety_percent = 1.0 - ow_percent;
-        if ow_percent.is_nan() {
-            ow_percent = 0.0;
-            variety_percent = 0.0;
-        }
+        Self::create(period, options, games, streams.data, surrounding)
+    }
 
-        let days_until_now = year.days_till_today();
-        let days_streamed = streamcounter::count(&streams.data);
-        let days_ditched = days_until_now - days_streamed;
-        let mut percent_ditched = days_ditched as f64 / days_until_now as f64;
-        if percent_ditched.is_nan() {
-            percent_ditched = 1.0;
-        }
+    /// Creates a model for an arbitrary period,
+    /// where the time per game is derived from the individual streams.
+    pub fn from_streams(
+        period: Period,
+        options: &ModelOptions,
+        streams: Vec<StreamData>,
+        surrounding: Surrounding,
+    ) -> Result<Self> {
+        Self::create(
+            period,
+            options,
+            GameModel::from_streams(&streams),
+            streams,
+            surrounding,
+        )
+    }
 
-        let (days, max_streamtime) = fill_days(year, &streams);
+    pub fn create(
+        period: Period,
+        options: &ModelOptions,
+        games: Vec<GameModel>,
+        streams: Vec<StreamData>,
+        surrounding: Surrounding,
+    ) -> Result<Self> {
+        let total_time_min = games.iter().map(|game| game.time_streamed_min).sum();
+        let diversity = Diversity::calculate(&games);
+        let ow_percent = diversity.top_share;
+        let variety_percent = diversity.variety(options.headline);
+
+        let days_until_now = period.days_till_today();
+        let days_streamed = streamcounter::count(&streams);
+        let days_ditched = days_until_now.saturating_sub(days_streamed);
+        let percent_ditched = ditched_share(days_ditched, days_until_now);
+
+        let (days, day_games, max_streamtime) = fill_days(period, &streams);
+        let period_start = period.bounds().0;
+        let excused_days = excuse_days(period, &options.breaks, &streams);
+        let days_excused = excused_days
+            .iter()
+            .take(days_until_now)
+            .filter(|excused| **excused)
+            .count();
+        let adjusted_days_ditched = days_ditched.saturating_sub(days_excused);
+        let adjusted_percent_ditched = ditched_share(
+            adjusted_days_ditched,
+            days_until_now.saturating_sub(days_excused),
+        );
+        let (start, end) = period.bounds();
+        let breaks = options
+            .breaks
+            .breaks
+            .iter()
+            .filter(|b| b.from < end.date_naive() && b.to >= start.date_naive())
+            .cloned()
+            .collect();
+        let projection = Projection::calculate(period, options, &games, &streams);
+        let enclosed = surrounding.enclose(&streams);
 
         Ok(Self {
             games,
@@ -119,29 +306,64 @@ impl StreamerModel {
             at_least_one_stream: total_time_min > 0,
             ow_percent,
             variety_percent,
-            are_we_variety: variety_percent >= 0.3,
+            are_we_variety: variety_percent >= options.variety_threshold,
+            diversity,
+            variety_description: options.headline.description(),
+            timeline: CategoryTimeline::calculate(period, &streams),
 
             days_ditched,
             days_until_now,
             percent_ditched,
+            days_excused,
+            adjusted_days_ditched,
+            adjusted_percent_ditched,
+            breaks,
 
             days,
+            excused_days,
+            day_games,
             max_streamtime,
-            start_of_year_offset: first_day_in_year(year.number())
-                .weekday()
-                .num_days_from_monday(),
+            start_offset: period_start.weekday().num_days_from_monday(),
 
-            year: match year {
-                Year::Current => Utc::now().year(),
-                Year::Last(year) => year,
-            },
+            year: period.year(),
+            period_label: period.label(),
+            period_start,
+            ongoing: period.is_ongoing(),
 
-            longest_ditch: LongestDitch::calculate(year, &streams.data),
+            longest_ditch: LongestDitch::calculate(period, &enclosed),
+            top_ditches: Ditch::top(period, &enclosed, TOP_DITCHES),
+            streaks: Streaks::calculate(period, &streams),
+            adherence: Adherence::calculate(
+                period,
+                &options.schedule,
+                options.timezone,
+                &options.breaks,
+                &streams,
+            ),
+
+            projection,
+            weekly: WeeklyHeatmap::calculate(period, options.timezone, &streams),
+            lengths: StreamLengths::calculate(&streams, options.short_stream_min),
+
+            streams,
+            surrounding,
+            fetched_at: Utc::now(),
         })
     }
+
+    /// The `streams` including the surrounding ones, so ditches aren't cut off at the bounds.
+    pub fn enclosed_streams(&self) -> Vec<StreamData> {
+        self.surrounding.enclose(&self.streams)
+    }
+
+    /// Whether the `day` (an index into `days`) had no stream and wasn't excused by a break.
+    pub fn is_ditched(&self, day: usize) -> bool {
+        self.days.get(day).is_some_and(|hours| *hours == 0.0)
+            && !self.excused_days.get(day).copied().unwrap_or_default()
+    }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GameModel {
     pub time_streamed_min: u64,
@@ -149,6 +371,52 @@ pub struct GameModel {
     pub category_image: String,
 }
 
+impl GameModel {
+    /// Splits the length of each stream evenly between the games played in it.
+    /// Streams without any known game are skipped.
+    pub fn from_streams(streams: &[StreamData]) -> Vec<Self> {
+        let mut games = HashMap::<&str, Self>::new();
+        for stream in streams {
+            let Some(per_game) = stream.length.checked_div(stream.games.len() as i64) else {
+                continue;
+            };
+            for game in &stream.games {
+                games
+                    .entry(&game.category)
+                    .or_insert_with(|| Self {
+                        time_streamed_min: 0,
+                        category: game.category.clone(),
+                        category_image: game.category_image.clone(),
+                    })
+                    .time_streamed_min += per_game.max(0) as u64;
+            }
+        }
+        let mut games = games.into_values().collect::<Vec<_>>();
+        games.sort_by_key(|game| Reverse(game.time_streamed_min));
+        games
+    }
+}
+
+impl GameModel {
+    /// Sums up the time per game of multiple periods.
+    pub fn merge<'a>(periods: impl Iterator<Item = &'a [GameModel]>) -> Vec<Self> {
+        let mut games = HashMap::<&str, Self>::new();
+        for game in periods.flatten() {
+            games
+                .entry(&game.category)
+                .or_insert_with(|| Self {
+                    time_streamed_min: 0,
+                    category: game.category.clone(),
+                    category_image: game.category_image.clone(),
+                })
+                .time_streamed_min += game.time_streamed_min;
+        }
+        let mut games = games.into_values().collect::<Vec<_>>();
+        games.sort_by_key(|game| Reverse(game.time_streamed_min));
+        games
+    }
+}
+
 impl TryFrom<sullygnome::GameData> for GameModel {
     type Error = anyhow::Error;
 
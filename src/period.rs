@@ -0,0 +1,196 @@
+use crate::datetime::{end_of_day, first_day_in_year, start_of_date};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Days, Duration, Months, NaiveDate, Utc, Weekday};
+use std::ops::RangeInclusive;
+
+/// Longest period that can be requested through `/last/{n}d` or `/range`.
+const MAX_DAYS: i64 = 5 * 366;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Period {
+    /// The current calendar year up until today.
+    CurrentYear,
+    /// A previous calendar year.
+    Year(i32),
+    Month {
+        year: i32,
+        month: u32,
+    },
+    /// An ISO week.
+    Week {
+        year: i32,
+        week: u32,
+    },
+    /// The last `n` days including today.
+    LastDays(u32),
+    /// Every day from `from` up to and including `to`.
+    Range {
+        from: NaiveDate,
+        to: NaiveDate,
+    },
+    /// Every tracked year up until today.
+    AllTime {
+        since: i32,
+    },
+}
+
+impl Period {
+    pub fn month(year: i32, month: u32) -> Result<Self> {
+        let period = Self::Month { year, month };
+        period
+            .checked_bounds()
+            .ok_or_else(|| anyhow!("Invalid month"))?;
+        Ok(period)
+    }
+
+    pub fn week(year: i32, week: u32) -> Result<Self> {
+        let period = Self::Week { year, week };
+        period
+            .checked_bounds()
+            .ok_or_else(|| anyhow!("Invalid week"))?;
+        Ok(period)
+    }
+
+    /// Parses the number of days in `/last/{n}d` (the `d` is optional).
+    pub fn last_days(days: &str) -> Result<Self> {
+        let days: u32 = days
+            .strip_suffix('d')
+            .unwrap_or(days)
+            .parse()
+            .map_err(|_| anyhow!("Expected a number of days like '30d'"))?;
+        if days == 0 || days as i64 > MAX_DAYS {
+            bail!("The number of days must be between 1 and {MAX_DAYS}");
+        }
+        Ok(Self::LastDays(days))
+    }
+
+    pub fn range(from: NaiveDate, to: NaiveDate) -> Result<Self> {
+        if from > to {
+            bail!("'from' must not be after 'to'");
+        }
+        if (to - from).num_days() >= MAX_DAYS {
+            bail!("A range can't be longer than {MAX_DAYS} days");
+        }
+        let period = Self::Range { from, to };
+        period
+            .checked_bounds()
+            .ok_or_else(|| anyhow!("The range is out of bounds"))?;
+        Ok(period)
+    }
+
+    /// Like [`Period::bounds`], but `None` if a month, week or range
+    /// is outside of the dates chrono supports.
+    fn checked_bounds(self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let (start, end) = match self {
+            Period::Month { year, month } => {
+                let start = NaiveDate::from_ymd_opt(year, month, 1)?;
+                (start, start.checked_add_months(Months::new(1))?)
+            }
+            Period::Week { year, week } => {
+                let start = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+                (start, start.checked_add_days(Days::new(7))?)
+            }
+            Period::Range { from, to } => (from, to.checked_add_days(Days::new(1))?),
+            _ => return Some(self.bounds()),
+        };
+        Some((start_of_date(start), start_of_date(end)))
+    }
+
+    /// The start (inclusive) and end (exclusive) of this period.
+    pub fn bounds(self) -> (DateTime<Utc>, DateTime<Utc>) {
+        match self {
+            Period::CurrentYear => {
+                let year = Utc::now().year();
+                (first_day_in_year(year), first_day_in_year(year + 1))
+            }
+            Period::Year(year) => (first_day_in_year(year), first_day_in_year(year + 1)),
+            Period::Month { .. } | Period::Week { .. } | Period::Range { .. } => self
+                .checked_bounds()
+                .expect("dates are validated when the period is created"),
+            Period::LastDays(days) => {
+                let end = end_of_day(Utc::now());
+                (end - Duration::days(days as i64), end)
+            }
+            Period::AllTime { since } => (first_day_in_year(since), end_of_day(Utc::now())),
+        }
+    }
+
+    /// The number of days in this period.
+    pub fn days(self) -> usize {
+        let (start, end) = self.bounds();
+        (end - start).num_days() as usize
+    }
+
+    /// The number of days in this period that already started.
+    pub fn days_till_today(self) -> usize {
+        let (start, end) = self.bounds();
+        let end = end.min(end_of_day(Utc::now()));
+        if start < end {
+            (end - start).num_days() as usize
+        } else {
+            0
+        }
+    }
+
+    pub fn is_ongoing(self) -> bool {
+        self.bounds().1 > Utc::now()
+    }
+
+    /// The year this period starts in.
+    pub fn year(self) -> i32 {
+        self.bounds().0.year()
+    }
+
+    /// All calendar years this period touches.
+    pub fn years(self) -> RangeInclusive<i32> {
+        let (start, end) = self.bounds();
+        start.year()..=(end - Duration::seconds(1)).year()
+    }
+
+    /// Used in sentences like "Were we variety {label}?"
+    pub fn label(self) -> String {
+        match self {
+            Period::CurrentYear => format!("in {}", self.year()),
+            Period::Year(year) => format!("in {year}"),
+            Period::Month { .. } => self.bounds().0.format("in %B %Y").to_string(),
+            Period::Week { year, week } => format!("in week {week} of {year}"),
+            Period::LastDays(days) => format!("in the last {days} days"),
+            Period::Range { from, to } => format!("from {from} to {to}"),
+            Period::AllTime { since } => format!("since {since}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::period::Period;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn bounds() {
+        let week = Period::week(2021, 1).unwrap();
+        assert_eq!(week.bounds().0.to_rfc3339(), "2021-01-04T00:00:00+00:00");
+        assert_eq!(week.days(), 7);
+
+        let month = Period::month(2024, 2).unwrap();
+        assert_eq!(month.days(), 29);
+        assert_eq!(month.years(), 2024..=2024);
+
+        let range = Period::range(
+            NaiveDate::from_ymd_opt(2022, 12, 24).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 1, 2).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(range.days(), 10);
+        assert_eq!(range.years(), 2022..=2023);
+
+        assert_eq!(Period::last_days("30d").unwrap().days(), 30);
+        assert!(Period::last_days("0d").is_err());
+        assert!(Period::month(2024, 13).is_err());
+        assert!(Period::week(2021, 53).is_err());
+        // out of chrono's range
+        assert!(Period::month(262143, 12).is_err());
+        assert!(Period::week(262143, 52).is_err());
+        assert!(Period::range(NaiveDate::MAX, NaiveDate::MAX).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    diversity::Diversity,
+    model::{GameModel, ModelOptions},
+    period::Period,
+    sullygnome::StreamData,
+};
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Number of days used to determine the current pace.
+const PACE_DAYS: i64 = 30;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Projection {
+    pub remaining_days: usize,
+    pub threshold: f64,
+    pub projected_total_min: u64,
+    pub projected_variety_percent: f64,
+    pub projected_are_we_variety: bool,
+    /// Variety hours needed to reach the threshold if nothing else was streamed.
+    pub variety_hours_needed: f64,
+    /// Variety hours needed until the end of the period
+    /// if the most played game keeps its current pace.
+    pub variety_hours_needed_by_end: f64,
+    pub variety_hours_per_day: f64,
+}
+
+impl Projection {
+    /// Extrapolates the time per game of the last [`PACE_DAYS`] days until the end of the year.
+    /// Returns `None` for any period but the current year.
+    pub fn calculate(
+        period: Period,
+        options: &ModelOptions,
+        games: &[GameModel],
+        streams: &[StreamData],
+    ) -> Option<Self> {
+        if period != Period::CurrentYear {
+            return None;
+        }
+        let remaining_days = period.days() - period.days_till_today();
+
+        let now = Utc::now();
+        let pace_start = (now - Duration::days(PACE_DAYS)).max(period.bounds().0);
+        let pace_days = ((now - pace_start).num_minutes() as f64 / (24.0 * 60.0)).max(1.0);
+        let recent = streams
+            .iter()
+            .filter(|stream| stream.overlaps(&pace_start, &now))
+            .cloned()
+            .collect::<Vec<_>>();
+        let scale = remaining_days as f64 / pace_days;
+
+        let mut projected = games
+            .iter()
+            .map(|game| (game.category.as_str(), game.time_streamed_min))
+            .collect::<HashMap<_, _>>();
+        let recent = GameModel::from_streams(&recent);
+        for game in &recent {
+            *projected.entry(&game.category).or_default() +=
+                (game.time_streamed_min as f64 * scale) as u64;
+        }
+        let projected = projected
+            .into_iter()
+            .map(|(category, time_streamed_min)| GameModel {
+                time_streamed_min,
+                category: category.to_owned(),
+                category_image: String::new(),
+            })
+            .collect::<Vec<_>>();
+        let projected_total_min = projected.iter().map(|game| game.time_streamed_min).sum();
+        let projected_variety_percent = Diversity::calculate(&projected).variety(options.headline);
+
+        // The hours needed are based on the most played game, as that's the only metric where
+        // additional variety time always moves the needle.
+        let total = games.iter().map(|game| game.time_streamed_min).sum::<u64>() as f64;
+        let top = games
+            .iter()
+            .map(|game| game.time_streamed_min)
+            .max()
+            .unwrap_or_default() as f64;
+        let top_pace = games
+            .iter()
+            .max_by_key(|game| game.time_streamed_min)
+            .and_then(|top| recent.iter().find(|game| game.category == top.category))
+            .map(|game| game.time_streamed_min as f64 * scale)
+            .unwrap_or_default();
+        let threshold = options.variety_threshold;
+        let variety_hours_needed = minutes_needed(threshold, total, top) / 60.0;
+        let variety_hours_needed_by_end =
+            minutes_needed(threshold, total + top_pace, top + top_pace) / 60.0;
+
+        Some(Self {
+            remaining_days,
+            threshold,
+            projected_total_min,
+            projected_variety_percent,
+            projected_are_we_variety: projected_variety_percent >= threshold,
+            variety_hours_needed,
+            variety_hours_needed_by_end,
+            variety_hours_per_day: variety_hours_needed_by_end / remaining_days.max(1) as f64,
+        })
+    }
+}
+
+/// Solves `(total - top + x) / (total + x) >= threshold` for `x`.
+fn minutes_needed(threshold: f64, total: f64, top: f64) -> f64 {
+    ((threshold * total - (total - top)) / (1.0 - threshold)).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{model::ModelOptions, period::Period, projection::Projection};
+
+    #[test]
+    fn only_projects_the_current_year() {
+        let options = ModelOptions::default();
+        assert!(Projection::calculate(Period::CurrentYear, &options, &[], &[]).is_some());
+        assert!(Projection::calculate(Period::LastDays(30), &options, &[], &[]).is_none());
+        assert!(
+            Projection::calculate(Period::AllTime { since: 2018 }, &options, &[], &[]).is_none()
+        );
+        assert!(Projection::calculate(Period::Year(2022), &options, &[], &[]).is_none());
+    }
+}
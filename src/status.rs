@@ -0,0 +1,216 @@
+use crate::{
+    data_actor::FetchOptions,
+    model::{ModelOptions, StreamerModel, Surrounding},
+    period::Period,
+    streamcounter::LongestDitch,
+    sullygnome::{self, GamesResponse, StreamData, StreamsResponse},
+    weekly::weekday_name,
+};
+use anyhow::{bail, Result};
+use chrono::{Datelike, Utc, Weekday};
+use futures::future;
+use std::time::Duration;
+
+/// Number of games listed in the summary.
+const TOP_GAMES: usize = 5;
+/// Shades of the heatmap, from a short to a long stream.
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+const DITCHED: char = '·';
+const EXCUSED: char = '-';
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Prints a summary of a year (the current one by default).
+///
+/// Like on the website, the streams right before and after the year are fetched as well,
+/// so ditches aren't cut off at its bounds.
+pub async fn status(
+    options: &ModelOptions,
+    fetch: FetchOptions,
+    year: Option<i32>,
+    json: bool,
+) -> Result<()> {
+    let channel_id = fetch.channel_id;
+    let current_year = Utc::now().year();
+    let year = year.unwrap_or(current_year);
+    if year > current_year {
+        bail!("{year} is in the future");
+    }
+    let (games, streams, surrounding) = future::try_join3(
+        sullygnome::get_all_of::<GamesResponse>(channel_id, year),
+        sullygnome::get_all_of::<StreamsResponse>(channel_id, year),
+        surrounding(fetch, year, current_year),
+    )
+    .await?;
+    let period = match year == current_year {
+        true => Period::CurrentYear,
+        false => Period::Year(year),
+    };
+    let model = StreamerModel::from_responses(period, options, games, streams, surrounding)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&model)?);
+    } else {
+        print_summary(&model);
+    }
+    Ok(())
+}
+
+/// The newest stream of the previous year (if it's tracked) and the oldest stream of the next year.
+async fn surrounding(fetch: FetchOptions, year: i32, current_year: i32) -> Result<Surrounding> {
+    let before = async {
+        match year > fetch.from_year {
+            true => newest_stream(fetch.channel_id, year - 1).await,
+            false => Ok(None),
+        }
+    };
+    let after = async {
+        match year < current_year {
+            true => oldest_stream(fetch.channel_id, year + 1).await,
+            false => Ok(None),
+        }
+    };
+    let (before, after) = future::try_join(before, after).await?;
+    Ok(Surrounding { before, after })
+}
+
+async fn newest_stream(channel_id: u64, year: i32) -> Result<Option<StreamData>> {
+    // streams are sorted from newest to oldest
+    let streams = sullygnome::get_streams(channel_id, year, 0).await?;
+    Ok(streams.data.into_iter().next())
+}
+
+async fn oldest_stream(channel_id: u64, year: i32) -> Result<Option<StreamData>> {
+    let first = sullygnome::get_streams(channel_id, year, 0).await?;
+    let last_page = (first.records_total - 1).max(0) / 100 * 100;
+    let streams = match last_page {
+        0 => first,
+        offset => sullygnome::get_streams(channel_id, year, offset).await?,
+    };
+    Ok(streams.data.into_iter().last())
+}
+
+fn print_summary(model: &StreamerModel) {
+    println!("{}", model.period_label);
+    println!(
+        "{}: {}% variety ({})",
+        match model.are_we_variety {
+            true => "Yes",
+            false => "No",
+        },
+        (model.variety_percent * 100.0).round(),
+        model.variety_description
+    );
+    println!(
+        "{}/{} days ditched ({}%)",
+        model.days_ditched,
+        model.days_until_now,
+        (model.percent_ditched * 100.0).round()
+    );
+    match &model.longest_ditch {
+        LongestDitch::Current { from } => println!(
+            "Longest ditch: ongoing since {}",
+            from.format("%Y-%m-%d %H:%M UTC")
+        ),
+        LongestDitch::Past { from, duration } => println!(
+            "Longest ditch: {duration} from {}",
+            from.format("%Y-%m-%d %H:%M UTC")
+        ),
+    }
+
+    println!("\nTop games:");
+    let width = model
+        .games
+        .iter()
+        .take(TOP_GAMES)
+        .map(|game| game.category.chars().count())
+        .max()
+        .unwrap_or_default();
+    for game in model.games.iter().take(TOP_GAMES) {
+        println!(
+            "  {:<width$}  {:>3}%  {}",
+            game.category,
+            (game.time_streamed_min as f64 * 100.0 / model.total_time_min.max(1) as f64).round(),
+            humantime::format_duration(Duration::from_secs(game.time_streamed_min * 60)),
+        );
+    }
+
+    println!("\n{}", heatmap(model));
+    println!(
+        "{DITCHED} ditched  {EXCUSED} break  {} streamed (short to long)",
+        SHADES.iter().collect::<String>()
+    );
+}
+
+/// The `days` as a grid with one row per weekday and one column per week.
+fn heatmap(model: &StreamerModel) -> String {
+    let offset = model.start_offset as usize;
+    let weeks = (offset + model.days.len()).div_ceil(7);
+    let mut out = String::new();
+    for (row, weekday) in WEEKDAYS.into_iter().enumerate() {
+        out.push_str(weekday_name(weekday));
+        out.push(' ');
+        for week in 0..weeks {
+            let day = (week * 7 + row).checked_sub(offset);
+            out.push(match day {
+                Some(day) if day < model.days_until_now => cell(model, day),
+                _ => ' ',
+            });
+        }
+        // the last weeks can be empty
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+    }
+    out
+}
+
+fn cell(model: &StreamerModel, day: usize) -> char {
+    let hours = model.days[day];
+    if hours > 0.0 {
+        let level = (hours / model.max_streamtime * SHADES.len() as f32).ceil() as usize;
+        SHADES[level.clamp(1, SHADES.len()) - 1]
+    } else if model.is_ditched(day) {
+        DITCHED
+    } else {
+        EXCUSED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        model::{ModelOptions, StreamerModel, Surrounding},
+        period::Period,
+        status::heatmap,
+    };
+
+    #[test]
+    fn draws_heatmap() {
+        let mut model = StreamerModel::create(
+            Period::Year(2024),
+            &ModelOptions::default(),
+            Vec::new(),
+            Vec::new(),
+            Surrounding::default(),
+        )
+        .unwrap();
+        // 2024 starts on a Monday
+        model.days_until_now = 9;
+        model.days[1] = 1.0;
+        model.days[8] = 4.0;
+        model.max_streamtime = 4.0;
+        let heatmap = heatmap(&model);
+        let rows = heatmap.lines().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[0], "Mon ··");
+        assert_eq!(rows[1], "Tue ░█");
+        assert_eq!(rows[2], "Wed ·");
+    }
+}
@@ -1,7 +1,7 @@
-use crate::{datetime::first_day_in_year, model::Year, sullygnome::StreamData};
-use chrono::{DateTime, Datelike, Duration, Utc};
+use crate::{period::Period, sullygnome::StreamData};
+use chrono::{DateTime, Days, Duration, NaiveDate, Utc};
 use serde::Serialize;
-use std::ops::Add;
+use std::{cmp::Reverse, collections::BTreeSet, ops::Add};
 
 #[derive(Serialize, Debug)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -15,6 +15,66 @@ pub enum LongestDitch {
     },
 }
 
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Streak {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub days: usize,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Streaks {
+    pub longest: Option<Streak>,
+    /// Only set if the period is ongoing and there was a stream today or yesterday.
+    pub current: Option<Streak>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Ditch {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub duration: String,
+    pub ongoing: bool,
+}
+
+impl Ditch {
+    fn new(from: DateTime<Utc>, to: DateTime<Utc>, ongoing: bool) -> Self {
+        Self {
+            from,
+            to,
+            duration: humantime::format_duration(
+                Duration::minutes((to - from).num_minutes())
+                    .to_std()
+                    .unwrap_or(std::time::Duration::from_secs(0)),
+            )
+            .to_string(),
+            ongoing,
+        }
+    }
+
+    /// The `n` longest gaps between streams, longest first.
+    pub fn top(period: Period, streams: &[StreamData], n: usize) -> Vec<Self> {
+        let mut ditches = streams
+            .windows(2)
+            .map(|pair| Self::new(pair[1].end_date_time(), pair[0].start_date_time, false))
+            .collect::<Vec<_>>();
+        if period.is_ongoing() {
+            // it's sorted from newest to oldest
+            let from = streams
+                .first()
+                .map(StreamData::end_date_time)
+                .unwrap_or(period.bounds().0);
+            ditches.push(Self::new(from, Utc::now(), true));
+        }
+        ditches.sort_by_key(|ditch| Reverse(ditch.to - ditch.from));
+        ditches.truncate(n);
+        ditches
+    }
+}
+
 pub fn count(streams: &[StreamData]) -> usize {
     streams
         .iter()
@@ -54,7 +114,7 @@ impl LongestDitch {
         }
     }
 
-    pub fn calculate(year: Year, streams: &[StreamData]) -> Self {
+    pub fn calculate(period: Period, streams: &[StreamData]) -> Self {
         let old_ditch = streams.windows(2).reduce(|accum, item| {
             if accum[1].duration_to(&accum[0]) > item[1].duration_to(&item[0]) {
                 accum
@@ -62,38 +122,81 @@ impl LongestDitch {
                 item
             }
         });
-        match year {
-            Year::Current => {
-                // it's sorted from newest to oldest
-                let last = match streams.first() {
-                    Some(last) => last,
-                    None => return Self::current(first_day_in_year(Utc::now().year())),
-                };
-
-                match old_ditch {
-                    Some(old_ditch)
-                        if last.duration_to_now() < old_ditch[1].duration_to(&old_ditch[0]) =>
-                    {
-                        Self::past(old_ditch)
-                    }
-                    _ => Self::current(last.end_date_time()),
+        if period.is_ongoing() {
+            // it's sorted from newest to oldest
+            let last = match streams.first() {
+                Some(last) => last,
+                None => return Self::current(period.bounds().0),
+            };
+
+            match old_ditch {
+                Some(old_ditch)
+                    if last.duration_to_now() < old_ditch[1].duration_to(&old_ditch[0]) =>
+                {
+                    Self::past(old_ditch)
                 }
+                _ => Self::current(last.end_date_time()),
             }
-            Year::Last(year) => match old_ditch {
+        } else {
+            match old_ditch {
                 Some(old_ditch) => Self::past(old_ditch),
                 // This shouldn't really happen, as there should be at least one stream.
                 None => Self::Past {
-                    from: first_day_in_year(year),
+                    from: period.bounds().0,
                     duration: "0".to_owned(),
                 },
-            },
+            }
+        }
+    }
+}
+
+impl Streaks {
+    pub fn calculate(period: Period, streams: &[StreamData]) -> Self {
+        let days = streams
+            .iter()
+            .flat_map(|it| {
+                it.start_date_time
+                    .date_naive()
+                    .iter_days()
+                    .take_while(|day| *day <= it.end_date_time().date_naive())
+            })
+            .collect::<BTreeSet<_>>();
+
+        let mut streaks = Vec::<Streak>::new();
+        for day in days {
+            match streaks.last_mut() {
+                Some(streak) if streak.to.checked_add_days(Days::new(1)) == Some(day) => {
+                    streak.to = day;
+                    streak.days += 1;
+                }
+                _ => streaks.push(Streak {
+                    from: day,
+                    to: day,
+                    days: 1,
+                }),
+            }
         }
+
+        let today = Utc::now().date_naive();
+        let current = streaks
+            .last()
+            .filter(|last| period.is_ongoing() && last.to >= today - Days::new(1))
+            .cloned();
+        // prefer the most recent streak if there are multiple with the same length
+        let longest = streaks.into_iter().rev().max_by_key(|streak| streak.days);
+
+        Self { longest, current }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{streamcounter::count, sullygnome::StreamData};
+    use crate::{
+        period::Period,
+        streamcounter::{count, Streak, Streaks},
+        sullygnome::StreamData,
+    };
+    use chrono::NaiveDate;
 
     #[test]
     fn it_works() {
@@ -101,22 +204,60 @@ mod tests {
             count(&[
                 StreamData {
                     start_date_time: "2022-01-04T14:08:05Z".parse().unwrap(),
-                    length: 0
+                    length: 0,
+                    games: Vec::new(),
+                    clamped: false,
                 },
                 StreamData {
                     start_date_time: "2022-01-02T14:08:05Z".parse().unwrap(),
                     length: 0,
+                    games: Vec::new(),
+                    clamped: false,
                 },
                 StreamData {
                     start_date_time: "2022-01-01T23:08:05Z".parse().unwrap(),
                     length: 0,
+                    games: Vec::new(),
+                    clamped: false,
                 },
                 StreamData {
                     start_date_time: "2022-01-01T14:08:05Z".parse().unwrap(),
-                    length: 0
+                    length: 0,
+                    games: Vec::new(),
+                    clamped: false,
                 },
             ]),
             3
         );
     }
+
+    #[test]
+    fn streaks() {
+        let stream = |start: &str, length| StreamData {
+            start_date_time: start.parse().unwrap(),
+            length,
+            games: Vec::new(),
+            clamped: false,
+        };
+        let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+        let streaks = Streaks::calculate(
+            Period::Year(2022),
+            &[
+                stream("2022-01-09T14:00:00Z", 60),
+                stream("2022-01-05T23:00:00Z", 120),
+                stream("2022-01-04T14:00:00Z", 60),
+                stream("2022-01-02T14:00:00Z", 60),
+                stream("2022-01-01T14:00:00Z", 60),
+            ],
+        );
+        assert_eq!(
+            streaks.longest,
+            Some(Streak {
+                from: date("2022-01-04"),
+                to: date("2022-01-06"),
+                days: 3
+            })
+        );
+        assert_eq!(streaks.current, None);
+    }
 }
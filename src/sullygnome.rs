@@ -1,15 +1,20 @@
 use anyhow::Result as AnyResult;
-use chrono::{DateTime, Datelike, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use futures::future;
 use lazy_static::lazy_static;
 use reqwest::{
     header::{self, HeaderMap, HeaderValue},
     Client,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::datetime::{end_of_day, first_day_in_year};
 
+/// The channel tracked by default (m0xyy).
+pub const DEFAULT_CHANNEL_ID: u64 = 3505649;
+/// The name of [`DEFAULT_CHANNEL_ID`], used in links to SullyGnome.
+pub const DEFAULT_CHANNEL_NAME: &str = "m0xyy";
+
 lazy_static! {
     static ref SULLYGNOME_CLIENT: Client = {
         let mut headers = HeaderMap::new();
@@ -59,12 +64,52 @@ impl StreamsResponse {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct StreamData {
     pub start_date_time: DateTime<Utc>,
     pub length: i64,
+    #[serde(
+        default,
+        rename = "gamesplayed",
+        deserialize_with = "deserialize_games_played"
+    )]
+    pub games: Vec<StreamGame>,
+    /// Whether the stream was cut off at the bounds of a period.
+    #[serde(skip)]
+    pub clamped: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamGame {
+    pub category: String,
+    pub category_image: String,
+}
+
+/// The streams table lists every game of a stream as `name|slug|image`,
+/// with the triples joined by `|` as well.
+pub fn parse_games_played(gamesplayed: &str) -> Vec<StreamGame> {
+    gamesplayed
+        .split('|')
+        .collect::<Vec<_>>()
+        .chunks_exact(3)
+        .map(|chunk| StreamGame {
+            category: chunk[0].to_owned(),
+            category_image: chunk[2].to_owned(),
+        })
+        .collect()
+}
+
+fn deserialize_games_played<'de, D>(deserializer: D) -> Result<Vec<StreamGame>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?
+        .as_deref()
+        .map(parse_games_played)
+        .unwrap_or_default())
 }
 
 impl StreamData {
@@ -84,6 +129,10 @@ impl StreamData {
         Utc::now() - self.end_date_time()
     }
 
+    pub fn overlaps(&self, start: &DateTime<Utc>, end: &DateTime<Utc>) -> bool {
+        self.start_date_time < *end && self.end_date_time() > *start
+    }
+
     pub fn day_iter(&self) -> StreamDayIter {
         StreamDayIter {
             start_date_time: self.start_date_time,
@@ -96,11 +145,13 @@ impl StreamData {
             let diff = *start - self.start_date_time;
             self.start_date_time = *start;
             self.length -= diff.num_minutes();
+            self.clamped = true;
         }
         let my_end = self.end_date_time();
         if my_end > *end {
             let diff = my_end - end;
             self.length -= diff.num_minutes();
+            self.clamped = true;
         }
         self.length = self.length.max(0);
     }
@@ -112,7 +163,7 @@ pub struct StreamDayIter {
 }
 
 impl Iterator for StreamDayIter {
-    type Item = (u32, f32);
+    type Item = (NaiveDate, f32);
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.start_date_time >= self.end_date_time {
@@ -122,11 +173,11 @@ impl Iterator for StreamDayIter {
         let (day, delta) = if self.start_date_time.date_naive() == self.end_date_time.date_naive() {
             let delta = self.end_date_time - self.start_date_time;
             self.start_date_time = self.end_date_time; // to return None next time
-            (self.start_date_time.ordinal0(), delta)
+            (self.start_date_time.date_naive(), delta)
         } else {
             let next_start = end_of_day(self.start_date_time);
             let delta = next_start - self.start_date_time;
-            let start = self.start_date_time.ordinal0();
+            let start = self.start_date_time.date_naive();
             self.start_date_time = next_start;
             (start, delta)
         };
@@ -138,7 +189,11 @@ impl Iterator for StreamDayIter {
 pub trait SullyResource: Sized {
     type Item;
 
-    fn get_it(year: i32, offset: i32) -> impl std::future::Future<Output = AnyResult<Self>>;
+    fn get_it(
+        channel_id: u64,
+        year: i32,
+        offset: i32,
+    ) -> impl std::future::Future<Output = AnyResult<Self>>;
 
     fn records(&self) -> i32;
     fn extend(&mut self, it: impl Iterator<Item = Self::Item>);
@@ -148,8 +203,12 @@ pub trait SullyResource: Sized {
 impl SullyResource for StreamsResponse {
     type Item = StreamData;
 
-    fn get_it(year: i32, offset: i32) -> impl std::future::Future<Output = AnyResult<Self>> {
-        get_streams(year, offset)
+    fn get_it(
+        channel_id: u64,
+        year: i32,
+        offset: i32,
+    ) -> impl std::future::Future<Output = AnyResult<Self>> {
+        get_streams(channel_id, year, offset)
     }
 
     fn records(&self) -> i32 {
@@ -168,8 +227,12 @@ impl SullyResource for StreamsResponse {
 impl SullyResource for GamesResponse {
     type Item = GameData;
 
-    fn get_it(year: i32, offset: i32) -> impl std::future::Future<Output = AnyResult<Self>> {
-        get_games(year, offset)
+    fn get_it(
+        channel_id: u64,
+        year: i32,
+        offset: i32,
+    ) -> impl std::future::Future<Output = AnyResult<Self>> {
+        get_games(channel_id, year, offset)
     }
 
     fn records(&self) -> i32 {
@@ -185,11 +248,11 @@ impl SullyResource for GamesResponse {
     }
 }
 
-pub async fn get_all_of<T: SullyResource>(year: i32) -> AnyResult<T> {
-    let mut base = T::get_it(year, 0).await?;
+pub async fn get_all_of<T: SullyResource>(channel_id: u64, year: i32) -> AnyResult<T> {
+    let mut base = T::get_it(channel_id, year, 0).await?;
     if base.records() > 100 {
         // (x + 99) / 100 is basically .div_ceil but that's unstable :(
-        let f = (1..((base.records() + 99) / 100)).map(|n| T::get_it(year, n * 100));
+        let f = (1..((base.records() + 99) / 100)).map(|n| T::get_it(channel_id, year, n * 100));
         base.extend(
             future::join_all(f)
                 .await
@@ -202,18 +265,18 @@ pub async fn get_all_of<T: SullyResource>(year: i32) -> AnyResult<T> {
     Ok(base)
 }
 
-pub async fn get_games(year: i32, offset: i32) -> AnyResult<GamesResponse> {
+pub async fn get_games(channel_id: u64, year: i32, offset: i32) -> AnyResult<GamesResponse> {
     Ok(SULLYGNOME_CLIENT
-        .get(format!("https://sullygnome.com/api/tables/channeltables/games/{year}/3505649/%20/1/2/desc/{offset}/100"))
+        .get(format!("https://sullygnome.com/api/tables/channeltables/games/{year}/{channel_id}/%20/1/2/desc/{offset}/100"))
         .send()
         .await?
         .json()
         .await?)
 }
 
-pub async fn get_streams(year: i32, offset: i32) -> AnyResult<StreamsResponse> {
+pub async fn get_streams(channel_id: u64, year: i32, offset: i32) -> AnyResult<StreamsResponse> {
     let mut res: StreamsResponse = SULLYGNOME_CLIENT
-        .get(format!("https://sullygnome.com/api/tables/channeltables/streams/{year}/3505649/%20/1/1/desc/{offset}/100"))
+        .get(format!("https://sullygnome.com/api/tables/channeltables/streams/{year}/{channel_id}/%20/1/1/desc/{offset}/100"))
         .send()
         .await?
         .json()
@@ -221,3 +284,28 @@ pub async fn get_streams(year: i32, offset: i32) -> AnyResult<StreamsResponse> {
     res.clamp_dates(year);
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::sullygnome::{parse_games_played, StreamGame};
+
+    #[test]
+    fn parses_games_played() {
+        assert_eq!(
+            parse_games_played(
+                "Overwatch 2|Overwatch_2|https://example.com/ow.jpg|Just Chatting|Just_Chatting|https://example.com/jc.jpg"
+            ),
+            vec![
+                StreamGame {
+                    category: "Overwatch 2".to_owned(),
+                    category_image: "https://example.com/ow.jpg".to_owned(),
+                },
+                StreamGame {
+                    category: "Just Chatting".to_owned(),
+                    category_image: "https://example.com/jc.jpg".to_owned(),
+                },
+            ]
+        );
+        assert!(parse_games_played("").is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{assets::Assets, helpers};
+use anyhow::Result;
+use chrono::Utc;
+use handlebars::{Handlebars, RenderError};
+use serde::Serialize;
+
+/// Settings shown on every page.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Site {
+    /// Name of the channel on SullyGnome.
+    pub channel_name: String,
+    /// How long the current year is cached (e.g. `10m`).
+    pub cache_time: String,
+    /// Rendered by `generate`, so only links to the pages it writes are shown.
+    pub static_site: bool,
+}
+
+/// The shared handlebars registry.
+pub struct Templates {
+    site: Site,
+    registry: Handlebars<'static>,
+}
+
+impl Templates {
+    /// In `dev` mode, the templates are read from the disk again on every render.
+    pub fn load(assets: Assets, site: Site, dev: bool) -> Result<Self> {
+        #[cfg(feature = "embed")]
+        if dev && matches!(assets, Assets::Embedded) {
+            anyhow::bail!("Embedded templates can't be reloaded, set a template_dir");
+        }
+        Ok(Self {
+            registry: registry(&assets, dev)?,
+            site,
+        })
+    }
+
+    pub fn render<T: Serialize>(&self, name: &str, data: &T) -> Result<String, RenderError> {
+        self.registry.render(name, data)
+    }
+
+    pub fn site(&self) -> &Site {
+        &self.site
+    }
+
+    /// Changes whenever the templates might have changed.
+    /// In dev mode, they're read on every render, so this changes on every call.
+    pub fn generation(&self) -> u64 {
+        match self.registry.dev_mode() {
+            true => Utc::now().timestamp_micros() as u64,
+            false => 0,
+        }
+    }
+}
+
+fn registry(assets: &Assets, dev: bool) -> Result<Handlebars<'static>> {
+    let mut handlebars = Handlebars::new();
+    // has to be set before the templates are registered
+    handlebars.set_dev_mode(dev);
+    assets.register_templates(&mut handlebars)?;
+    handlebars.register_helper("bar-width", Box::new(helpers::bar_width));
+    handlebars.register_helper("humanize-min", Box::new(helpers::humanize_min));
+    handlebars.register_helper("round-percent", Box::new(helpers::rounded_percent));
+    handlebars.register_helper("format-hours", Box::new(helpers::format_hours));
+    handlebars.register_helper("fixed", Box::new(helpers::fixed));
+    handlebars.register_helper("signed", Box::new(helpers::signed));
+    handlebars.register_helper("url-component", Box::new(helpers::url_component));
+    Ok(handlebars)
+}
@@ -0,0 +1,191 @@
+use crate::{period::Period, sullygnome::StreamData};
+use chrono::{Datelike, Duration, NaiveDate, Timelike, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// The timezone the streamer lives in by default.
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::London;
+
+/// A weekday counts as a usual stream day if there was a stream on at least this share of them.
+const USUAL_DAY_SHARE: f64 = 0.5;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyHeatmap {
+    pub days: Vec<WeekdayModel>,
+    /// The highest number of hours in any slot.
+    pub max_hours: f32,
+    pub timezone: &'static str,
+    pub schedule: Schedule,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekdayModel {
+    pub name: &'static str,
+    /// Hours streamed in each hour of the day in the channel's timezone.
+    pub hours: Vec<f32>,
+    /// The share of these weekdays in the period with a stream.
+    pub streamed_share: f64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub days: Vec<&'static str>,
+    /// The median start time (`HH:MM`) of streams on the usual days.
+    pub usual_start: Option<String>,
+    pub summary: String,
+}
+
+impl WeeklyHeatmap {
+    pub fn calculate(period: Period, timezone: Tz, streams: &[StreamData]) -> Self {
+        let mut hours = [[0.0f32; 24]; 7];
+        let mut stream_days = HashSet::<NaiveDate>::new();
+        for stream in streams {
+            let end = stream.end_date_time();
+            let mut time = stream.start_date_time;
+            stream_days.insert(time.with_timezone(&timezone).date_naive());
+            while time < end {
+                let local_time = time.with_timezone(&timezone);
+                let next = (time + Duration::minutes(60 - local_time.minute() as i64)
+                    - Duration::seconds(local_time.second() as i64))
+                .min(end);
+                hours[local_time.weekday().num_days_from_monday() as usize]
+                    [local_time.hour() as usize] += (next - time).num_minutes() as f32 / 60.0;
+                time = next;
+            }
+        }
+
+        let (start, _) = period.bounds();
+        let mut occurrences = [0usize; 7];
+        let mut streamed = [0usize; 7];
+        for day in start
+            .date_naive()
+            .iter_days()
+            .take(period.days_till_today())
+        {
+            let weekday = day.weekday().num_days_from_monday() as usize;
+            occurrences[weekday] += 1;
+            if stream_days.contains(&day) {
+                streamed[weekday] += 1;
+            }
+        }
+
+        let days = WEEKDAYS
+            .iter()
+            .enumerate()
+            .map(|(i, weekday)| WeekdayModel {
+                name: weekday_name(*weekday),
+                hours: hours[i].to_vec(),
+                streamed_share: if occurrences[i] == 0 {
+                    0.0
+                } else {
+                    streamed[i] as f64 / occurrences[i] as f64
+                },
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            max_hours: hours.iter().flatten().copied().fold(0.1, f32::max),
+            timezone: timezone.name(),
+            schedule: Schedule::from_days(&days, timezone, streams),
+            days,
+        }
+    }
+}
+
+impl Schedule {
+    fn from_days(days: &[WeekdayModel], timezone: Tz, streams: &[StreamData]) -> Self {
+        let usual = WEEKDAYS
+            .iter()
+            .zip(days)
+            .filter(|(_, day)| day.streamed_share >= USUAL_DAY_SHARE)
+            .map(|(weekday, _)| *weekday)
+            .collect::<Vec<_>>();
+
+        let mut starts = streams
+            .iter()
+            .map(|stream| stream.start_date_time.with_timezone(&timezone))
+            .filter(|start| usual.contains(&start.weekday()))
+            .map(|start| start.hour() * 60 + start.minute())
+            .collect::<Vec<_>>();
+        starts.sort_unstable();
+        let usual_start = starts
+            .get(starts.len() / 2)
+            .map(|minutes| format!("{:02}:{:02}", minutes / 60, minutes % 60));
+
+        let days = usual
+            .iter()
+            .map(|weekday| weekday_name(*weekday))
+            .collect::<Vec<_>>();
+        let summary = match (days.split_last(), &usual_start) {
+            (Some((last, [])), Some(start)) => {
+                format!("Usually streams on {last} around {start}.")
+            }
+            (Some((last, rest)), Some(start)) => format!(
+                "Usually streams on {} and {last} around {start}.",
+                rest.join(", ")
+            ),
+            _ => "No usual schedule.".to_owned(),
+        };
+
+        Self {
+            days,
+            usual_start,
+            summary,
+        }
+    }
+}
+
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Mon",
+        Weekday::Tue => "Tue",
+        Weekday::Wed => "Wed",
+        Weekday::Thu => "Thu",
+        Weekday::Fri => "Fri",
+        Weekday::Sat => "Sat",
+        Weekday::Sun => "Sun",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        period::Period,
+        sullygnome::StreamData,
+        weekly::{WeeklyHeatmap, DEFAULT_TIMEZONE},
+    };
+
+    #[test]
+    fn splits_streams_into_hours() {
+        let heatmap = WeeklyHeatmap::calculate(
+            Period::Year(2022),
+            DEFAULT_TIMEZONE,
+            &[StreamData {
+                // Tuesday, 18:30 in London (GMT)
+                start_date_time: "2022-01-04T18:30:00Z".parse().unwrap(),
+                length: 120,
+                games: Vec::new(),
+                clamped: false,
+            }],
+        );
+        let tuesday = &heatmap.days[1].hours;
+        assert_eq!(tuesday[18], 0.5);
+        assert_eq!(tuesday[19], 1.0);
+        assert_eq!(tuesday[20], 0.5);
+        assert_eq!(heatmap.schedule.summary, "No usual schedule.");
+    }
+}